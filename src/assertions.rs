@@ -0,0 +1,362 @@
+use crate::config::{Assertion, HeaderAssertion, JsonAssertion, Pattern};
+use reqwest::header::{HeaderMap, HeaderName};
+use serde_json::Value;
+
+/// Checks that an assertion can be evaluated, e.g. that its regex compiles.
+pub fn validate(assertion: &Assertion) -> Result<(), String> {
+    match assertion {
+        Assertion::Regex(pattern) => compiles(pattern),
+        Assertion::JsonPath(json) => {
+            parse_path(&json.path)?;
+            json.regex.as_ref().map_or(Ok(()), compiles)
+        }
+        Assertion::Header(header) => {
+            HeaderName::from_bytes(header.name.as_bytes())
+                .map_err(|_| format!("invalid header name '{}'", header.name))?;
+            header.regex.as_ref().map_or(Ok(()), compiles)
+        }
+        _ => Ok(()),
+    }
+}
+
+fn compiles(pattern: &Pattern) -> Result<(), String> {
+    pattern.regex().map(|_| ()).map_err(str::to_string)
+}
+
+/// Evaluates `assertions` against a response body, returning one message per
+/// failed assertion.
+pub fn check_body(assertions: &[Assertion], body: &str) -> Vec<String> {
+    // Only parse the body as JSON if something needs it
+    let mut json: Option<Result<Value, String>> = None;
+
+    assertions
+        .iter()
+        .filter_map(|assertion| match assertion {
+            Assertion::JsonPath(check) => {
+                let parsed = json.get_or_insert_with(|| {
+                    serde_json::from_str(body)
+                        .map_err(|e| format!("Response is not valid JSON: {}", e))
+                });
+                match parsed {
+                    Ok(value) => check_json(check, value),
+                    Err(e) => Some(e.clone()),
+                }
+            }
+            _ => check_one(assertion, body),
+        })
+        .collect()
+}
+
+/// Evaluates the header assertions in `assertions` against response headers.
+pub fn check_headers(assertions: &[Assertion], headers: &HeaderMap) -> Vec<String> {
+    assertions
+        .iter()
+        .filter_map(|assertion| match assertion {
+            Assertion::Header(check) => check_header(check, headers),
+            _ => None,
+        })
+        .collect()
+}
+
+fn check_header(check: &HeaderAssertion, headers: &HeaderMap) -> Option<String> {
+    let name = &check.name;
+    let values: Vec<&str> = headers
+        .get_all(name.as_str())
+        .iter()
+        .map(|value| value.to_str().unwrap_or(""))
+        .collect();
+
+    match (values.is_empty(), check.exists) {
+        (false, Some(false)) => {
+            return Some(format!(
+                "Header '{}' present but expected to be absent",
+                name
+            ))
+        }
+        (true, Some(false)) => return None,
+        (true, _) => return Some(format!("Header '{}' missing from response", name)),
+        (false, _) => {}
+    }
+
+    if let Some(expected) = &check.equals {
+        if !values.iter().any(|value| value == expected) {
+            return Some(format!(
+                "Header '{}': expected '{}', got '{}'",
+                name,
+                expected,
+                values.join(", ")
+            ));
+        }
+    }
+
+    if let Some(pattern) = &check.regex {
+        match pattern.regex() {
+            Ok(re) if values.iter().any(|value| re.is_match(value)) => {}
+            Ok(_) => {
+                return Some(format!(
+                    "Header '{}': '{}' does not match regex '{}'",
+                    name,
+                    values.join(", "),
+                    pattern.as_str()
+                ))
+            }
+            Err(e) => return Some(e.to_string()),
+        }
+    }
+
+    None
+}
+
+fn check_one(assertion: &Assertion, body: &str) -> Option<String> {
+    match assertion {
+        Assertion::Contains(expected) if !body.contains(expected.as_str()) => Some(format!(
+            "Expected content '{}' not found in response",
+            expected
+        )),
+        Assertion::NotContains(forbidden) if body.contains(forbidden.as_str()) => Some(format!(
+            "Forbidden content '{}' found in response",
+            forbidden
+        )),
+        Assertion::ContainsIgnoreCase(expected)
+            if !body.to_lowercase().contains(&expected.to_lowercase()) =>
+        {
+            Some(format!(
+                "Expected content '{}' (case-insensitive) not found in response",
+                expected
+            ))
+        }
+        Assertion::Regex(pattern) => match pattern.regex() {
+            Ok(re) if re.is_match(body) => None,
+            Ok(_) => Some(format!(
+                "Response does not match regex '{}'",
+                pattern.as_str()
+            )),
+            Err(e) => Some(e.to_string()),
+        },
+        Assertion::BodyMinBytes(min) if body.len() < *min => Some(format!(
+            "Response body is {} bytes, expected at least {}",
+            body.len(),
+            min
+        )),
+        Assertion::BodyMaxBytes(max) if body.len() > *max => Some(format!(
+            "Response body is {} bytes, expected at most {}",
+            body.len(),
+            max
+        )),
+        _ => None,
+    }
+}
+
+fn check_json(check: &JsonAssertion, body: &Value) -> Option<String> {
+    let path = &check.path;
+    let value = match lookup(body, path) {
+        Ok(value) => value,
+        Err(e) => return Some(e),
+    };
+
+    let value = match (value, check.exists) {
+        (Some(_), Some(false)) => return Some(format!("{}: expected not to exist", path)),
+        (None, Some(false)) => return None,
+        (None, _) => return Some(format!("{}: not found in response", path)),
+        (Some(value), _) => value,
+    };
+
+    if let Some(expected) = &check.equals {
+        if value != expected {
+            return Some(format!("{}: expected {}, got {}", path, expected, value));
+        }
+    }
+
+    if check.less_than.is_some() || check.greater_than.is_some() {
+        let Some(number) = value.as_f64() else {
+            return Some(format!("{}: expected a number, got {}", path, value));
+        };
+        if let Some(limit) = check.less_than {
+            if number >= limit {
+                return Some(format!("{}: {} is not less than {}", path, number, limit));
+            }
+        }
+        if let Some(limit) = check.greater_than {
+            if number <= limit {
+                return Some(format!(
+                    "{}: {} is not greater than {}",
+                    path, number, limit
+                ));
+            }
+        }
+    }
+
+    if let Some(pattern) = &check.regex {
+        // Match strings by their contents rather than their JSON encoding
+        let text = match value {
+            Value::String(s) => s.clone(),
+            other => other.to_string(),
+        };
+        match pattern.regex() {
+            Ok(re) if re.is_match(&text) => {}
+            Ok(_) => {
+                return Some(format!(
+                    "{}: {} does not match regex '{}'",
+                    path,
+                    value,
+                    pattern.as_str()
+                ))
+            }
+            Err(e) => return Some(e.to_string()),
+        }
+    }
+
+    None
+}
+
+enum Segment {
+    Key(String),
+    Index(usize),
+}
+
+fn lookup<'a>(value: &'a Value, path: &str) -> Result<Option<&'a Value>, String> {
+    if path.is_empty() || path.starts_with('/') {
+        return Ok(value.pointer(path));
+    }
+
+    let mut current = value;
+    for segment in parse_path(path)? {
+        let next = match segment {
+            Segment::Key(key) => current.get(key.as_str()),
+            Segment::Index(index) => current.get(index),
+        };
+        match next {
+            Some(next) => current = next,
+            None => return Ok(None),
+        }
+    }
+    Ok(Some(current))
+}
+
+/// Parses the JSONPath subset `$`, `.key`, `['key']` and `[index]`. JSON
+/// pointers are passed through untouched.
+fn parse_path(path: &str) -> Result<Vec<Segment>, String> {
+    if path.is_empty() || path.starts_with('/') {
+        return Ok(Vec::new());
+    }
+
+    let invalid = |reason: &str| format!("invalid JSON path '{}': {}", path, reason);
+    let rest = path
+        .strip_prefix('$')
+        .ok_or_else(|| invalid("must start with '$' or '/'"))?;
+    let mut chars = rest.chars().peekable();
+    let mut segments = Vec::new();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '.' => {
+                let mut key = String::new();
+                while let Some(&c) = chars.peek() {
+                    if c == '.' || c == '[' {
+                        break;
+                    }
+                    key.push(c);
+                    chars.next();
+                }
+                if key.is_empty() {
+                    return Err(invalid("empty key"));
+                }
+                segments.push(Segment::Key(key));
+            }
+            '[' => {
+                let mut inner = String::new();
+                let mut closed = false;
+                for c in chars.by_ref() {
+                    if c == ']' {
+                        closed = true;
+                        break;
+                    }
+                    inner.push(c);
+                }
+                if !closed {
+                    return Err(invalid("unterminated '['"));
+                }
+                let quoted = inner
+                    .strip_prefix('\'')
+                    .and_then(|s| s.strip_suffix('\''))
+                    .or_else(|| inner.strip_prefix('"').and_then(|s| s.strip_suffix('"')));
+                match quoted {
+                    Some(key) => segments.push(Segment::Key(key.to_string())),
+                    None => {
+                        let index = inner
+                            .parse()
+                            .map_err(|_| invalid("expected an index or quoted key in []"))?;
+                        segments.push(Segment::Index(index));
+                    }
+                }
+            }
+            _ => return Err(invalid("expected '.' or '['")),
+        }
+    }
+
+    Ok(segments)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn keys(path: &str) -> Vec<String> {
+        parse_path(path)
+            .unwrap()
+            .into_iter()
+            .map(|segment| match segment {
+                Segment::Key(key) => format!(".{}", key),
+                Segment::Index(index) => format!("[{}]", index),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn parses_jsonpath_segments() {
+        assert!(keys("$").is_empty());
+        assert_eq!(keys("$.db.latency_ms"), [".db", ".latency_ms"]);
+        assert_eq!(keys("$.items[0].id"), [".items", "[0]", ".id"]);
+        assert_eq!(keys("$['a.b'][\"c d\"][12]"), [".a.b", ".c d", "[12]"]);
+    }
+
+    #[test]
+    fn passes_json_pointers_through() {
+        assert!(keys("").is_empty());
+        assert!(keys("/db/latency_ms").is_empty());
+    }
+
+    #[test]
+    fn rejects_malformed_paths() {
+        let error = |path: &str| parse_path(path).err().unwrap();
+        assert_eq!(
+            error("db.latency"),
+            "invalid JSON path 'db.latency': must start with '$' or '/'"
+        );
+        assert_eq!(error("$.a..b"), "invalid JSON path '$.a..b': empty key");
+        assert_eq!(
+            error("$.items[0"),
+            "invalid JSON path '$.items[0': unterminated '['"
+        );
+        assert_eq!(
+            error("$.items[x]"),
+            "invalid JSON path '$.items[x]': expected an index or quoted key in []"
+        );
+        assert_eq!(
+            error("$items"),
+            "invalid JSON path '$items': expected '.' or '['"
+        );
+    }
+
+    #[test]
+    fn looks_up_paths_and_pointers() {
+        let body = json!({ "db": { "latency_ms": 12 }, "items": [{ "id": "a" }, { "id": "b" }] });
+        assert_eq!(lookup(&body, "$.db.latency_ms").unwrap(), Some(&json!(12)));
+        assert_eq!(lookup(&body, "$.items[1].id").unwrap(), Some(&json!("b")));
+        assert_eq!(lookup(&body, "/items/0/id").unwrap(), Some(&json!("a")));
+        assert_eq!(lookup(&body, "$").unwrap(), Some(&body));
+        assert_eq!(lookup(&body, "$.items[5]").unwrap(), None);
+        assert_eq!(lookup(&body, "$.db.missing").unwrap(), None);
+    }
+}
@@ -1,4 +1,9 @@
-use serde::{Deserialize, Serialize};
+use crate::inherit;
+use crate::interpolate;
+use crate::validate::{self, LineIndex, Problem, Severity};
+use regex::Regex;
+use reqwest::Method;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use std::collections::HashMap;
 use std::time::Duration;
 
@@ -8,30 +13,171 @@ pub struct Config {
     pub settings: Settings,
     #[serde(default)]
     pub alerts: Vec<Alert>,
+    /// Shared target fields, keyed by name, that targets pull in with `group`
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub groups: HashMap<String, serde_yaml::Value>,
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
 pub struct Target {
     pub name: String,
     pub url: String,
-    #[serde(default = "default_method")]
-    pub method: String,
+    /// Name of the entry in `Config.groups` this target inherits from
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub group: Option<String>,
+    #[serde(default = "default_method", with = "http_method")]
+    pub method: Method,
     #[serde(default)]
     pub headers: HashMap<String, String>,
+    /// Raw request body, sent as form data unless a Content-Type header is set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub body: Option<String>,
+    /// Path to a file whose contents are sent as the raw request body
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub body_file: Option<String>,
+    /// Request body sent as JSON
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub json: Option<serde_json::Value>,
     #[serde(default)]
     pub expected_status: Vec<u16>,
     #[serde(default)]
     pub expected_content: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub assertions: Vec<Assertion>,
     #[serde(default = "default_timeout")]
     pub timeout_seconds: f64,
     #[serde(default = "default_interval")]
     pub interval_seconds: f64,
+    /// Overrides `Settings.max_consecutive_failures` for this target
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_consecutive_failures: Option<u32>,
+    /// Overrides individual fields of `Settings.health` for this target
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub health: Option<HealthOverrides>,
+    /// Names of the alerts this target notifies; all of them when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub alerts: Option<Vec<String>>,
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize)]
+/// A request body together with the Content-Type it implies. Raw data
+/// follows curl's `--data` and is sent as form data.
+#[derive(Debug, Clone)]
+pub struct RequestBody {
+    pub content_type: &'static str,
+    pub data: Vec<u8>,
+}
+
+impl RequestBody {
+    pub fn raw(data: impl Into<Vec<u8>>) -> Self {
+        Self {
+            content_type: "application/x-www-form-urlencoded",
+            data: data.into(),
+        }
+    }
+
+    pub fn json(value: &serde_json::Value) -> Self {
+        Self {
+            content_type: "application/json",
+            data: value.to_string().into_bytes(),
+        }
+    }
+}
+
+/// A check on the response; all of a target's assertions must pass.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Assertion {
+    Contains(String),
+    NotContains(String),
+    ContainsIgnoreCase(String),
+    Regex(Pattern),
+    BodyMinBytes(usize),
+    BodyMaxBytes(usize),
+    JsonPath(JsonAssertion),
+    Header(HeaderAssertion),
+}
+
+/// Checks on a value in a JSON response body. `path` is either a JSON pointer
+/// (`/db/latency_ms`) or a simple JSONPath (`$.db.latency_ms`, `$.items[0]`).
+/// With no other condition set the value only has to exist.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+pub struct JsonAssertion {
+    pub path: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub equals: Option<serde_json::Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exists: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub less_than: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub greater_than: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub regex: Option<Pattern>,
+}
+
+/// Checks on a response header. With no condition set the header only has
+/// to be present; with several values, any one of them may satisfy it.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+pub struct HeaderAssertion {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub equals: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exists: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub regex: Option<Pattern>,
+}
+
+/// A regex from an assertion, compiled once when the config is loaded rather
+/// than on every check. An invalid pattern still loads so that `validate` can
+/// report it with its location.
+#[derive(Debug, Clone)]
+pub struct Pattern {
+    source: String,
+    compiled: Result<Regex, String>,
+}
+
+impl Pattern {
+    pub fn new(source: impl Into<String>) -> Self {
+        let source = source.into();
+        let compiled = Regex::new(&source).map_err(|e| format!("invalid regex '{}': {}", source, e));
+        Self { source, compiled }
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.source
+    }
+
+    /// The compiled regex, or why the pattern doesn't compile.
+    pub fn regex(&self) -> Result<&Regex, &str> {
+        self.compiled.as_ref().map_err(String::as_str)
+    }
+}
+
+impl PartialEq for Pattern {
+    fn eq(&self, other: &Self) -> bool {
+        self.source == other.source
+    }
+}
+
+impl Serialize for Pattern {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.source)
+    }
+}
+
+impl<'de> Deserialize<'de> for Pattern {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer).map(Pattern::new)
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
 pub struct Settings {
+    /// Interval for targets that set neither `interval_seconds` nor a group value
     #[serde(default = "default_interval")]
     pub default_interval: f64,
+    /// Timeout for targets that set neither `timeout_seconds` nor a group value
     #[serde(default = "default_timeout")]
     pub default_timeout: f64,
     #[serde(default = "default_max_failures")]
@@ -39,11 +185,79 @@ pub struct Settings {
     #[serde(default = "default_health_window")]
     pub health_check_window_minutes: u32,
     #[serde(default)]
+    pub health: HealthThresholds,
+    #[serde(default)]
     pub output_format: OutputFormat,
     #[serde(default)]
     pub enable_colors: bool,
     #[serde(default)]
     pub log_file: Option<String>,
+    #[serde(default)]
+    pub log_rotation: LogRotation,
+    #[serde(default)]
+    pub verbose: bool,
+    /// Address to serve Prometheus metrics on, e.g. "0.0.0.0:9115"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metrics_listen: Option<String>,
+    /// Address to serve the HTML status page and JSON API on, e.g. "0.0.0.0:8080"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status_listen: Option<String>,
+}
+
+/// How uptime and response time turn into a status and a 0-1 health score:
+/// `score = uptime * uptime_weight + response_time_score * response_time_weight`.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+pub struct HealthThresholds {
+    /// Minimum uptime percentage to be Healthy
+    #[serde(default = "default_healthy_uptime")]
+    pub healthy_uptime: f64,
+    /// Minimum uptime percentage to be Degraded rather than Unhealthy
+    #[serde(default = "default_degraded_uptime")]
+    pub degraded_uptime: f64,
+    #[serde(default = "default_uptime_weight")]
+    pub uptime_weight: f64,
+    #[serde(default = "default_response_time_weight")]
+    pub response_time_weight: f64,
+    /// Score for an average response time at or under each `max_ms`, checked in order
+    #[serde(default = "default_response_time_scores")]
+    pub response_time_scores: Vec<ResponseTimeScore>,
+    /// Score for an average response time above every `max_ms`
+    #[serde(default = "default_slow_score")]
+    pub slow_score: f64,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+pub struct ResponseTimeScore {
+    pub max_ms: u64,
+    pub score: f64,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq)]
+pub struct HealthOverrides {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub healthy_uptime: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub degraded_uptime: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub uptime_weight: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub response_time_weight: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub response_time_scores: Option<Vec<ResponseTimeScore>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub slow_score: Option<f64>,
+}
+
+/// When to rotate `log_file`. Rotated files are renamed to `<log_file>.1`,
+/// `<log_file>.2`, ... with the oldest beyond `keep` deleted.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+pub struct LogRotation {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_size_mb: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_age_hours: Option<u64>,
+    #[serde(default = "default_log_keep")]
+    pub keep: u32,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -60,11 +274,12 @@ pub struct Alert {
 pub enum AlertTrigger {
     ConsecutiveFailures(u32),
     ResponseTimeMs(u64),
+    /// Health score on the 0-100 scale shown in the status summary.
     HealthScoreBelow(f64),
     CertExpiringDays(u32),
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize, Default)]
+#[derive(Debug, Clone, Deserialize, Serialize, Default, PartialEq)]
 #[serde(rename_all = "snake_case")]
 pub enum OutputFormat {
     #[default]
@@ -74,8 +289,27 @@ pub enum OutputFormat {
     Prometheus,
 }
 
-fn default_method() -> String {
-    "GET".to_string()
+fn default_method() -> Method {
+    Method::GET
+}
+
+/// Parses an HTTP method, accepting extension methods such as `PURGE` or
+/// `PROPFIND`. Methods are upper-cased first, so `get` means `GET`.
+pub fn parse_method(method: &str) -> Result<Method, String> {
+    Method::from_bytes(method.to_uppercase().as_bytes()).map_err(|_| format!("invalid HTTP method '{}'", method))
+}
+
+mod http_method {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(method: &Method, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(method.as_str())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Method, D::Error> {
+        let method = String::deserialize(deserializer)?;
+        parse_method(&method).map_err(serde::de::Error::custom)
+    }
 }
 
 fn default_timeout() -> f64 {
@@ -94,10 +328,42 @@ fn default_health_window() -> u32 {
     60
 }
 
+fn default_healthy_uptime() -> f64 {
+    99.0
+}
+
+fn default_degraded_uptime() -> f64 {
+    95.0
+}
+
+fn default_uptime_weight() -> f64 {
+    0.7
+}
+
+fn default_response_time_weight() -> f64 {
+    0.3
+}
+
+fn default_response_time_scores() -> Vec<ResponseTimeScore> {
+    vec![
+        ResponseTimeScore { max_ms: 500, score: 1.0 },
+        ResponseTimeScore { max_ms: 2000, score: 0.8 },
+        ResponseTimeScore { max_ms: 5000, score: 0.5 },
+    ]
+}
+
+fn default_slow_score() -> f64 {
+    0.2
+}
+
 fn default_cooldown() -> u32 {
     30
 }
 
+fn default_log_keep() -> u32 {
+    5
+}
+
 impl Default for Settings {
     fn default() -> Self {
         Self {
@@ -105,18 +371,154 @@ impl Default for Settings {
             default_timeout: default_timeout(),
             max_consecutive_failures: default_max_failures(),
             health_check_window_minutes: default_health_window(),
+            health: HealthThresholds::default(),
             output_format: OutputFormat::default(),
             enable_colors: true,
             log_file: None,
+            log_rotation: LogRotation::default(),
+            verbose: false,
+            metrics_listen: None,
+            status_listen: None,
+        }
+    }
+}
+
+impl Default for HealthThresholds {
+    fn default() -> Self {
+        Self {
+            healthy_uptime: default_healthy_uptime(),
+            degraded_uptime: default_degraded_uptime(),
+            uptime_weight: default_uptime_weight(),
+            response_time_weight: default_response_time_weight(),
+            response_time_scores: default_response_time_scores(),
+            slow_score: default_slow_score(),
+        }
+    }
+}
+
+impl HealthThresholds {
+    pub fn with_overrides(&self, overrides: &HealthOverrides) -> Self {
+        Self {
+            healthy_uptime: overrides.healthy_uptime.unwrap_or(self.healthy_uptime),
+            degraded_uptime: overrides.degraded_uptime.unwrap_or(self.degraded_uptime),
+            uptime_weight: overrides.uptime_weight.unwrap_or(self.uptime_weight),
+            response_time_weight: overrides.response_time_weight.unwrap_or(self.response_time_weight),
+            response_time_scores: overrides
+                .response_time_scores
+                .clone()
+                .unwrap_or_else(|| self.response_time_scores.clone()),
+            slow_score: overrides.slow_score.unwrap_or(self.slow_score),
+        }
+    }
+
+    pub fn response_time_score(&self, avg_response_time: Duration) -> f64 {
+        let ms = avg_response_time.as_millis() as u64;
+        self.response_time_scores
+            .iter()
+            .find(|bucket| ms <= bucket.max_ms)
+            .map_or(self.slow_score, |bucket| bucket.score)
+    }
+}
+
+impl Default for LogRotation {
+    fn default() -> Self {
+        Self {
+            max_size_mb: None,
+            max_age_hours: None,
+            keep: default_log_keep(),
+        }
+    }
+}
+
+impl Target {
+    /// The request body to send, reading `body_file` fresh on every call.
+    pub fn request_body(&self) -> Result<Option<RequestBody>, String> {
+        if let Some(body) = &self.body {
+            return Ok(Some(RequestBody::raw(body.as_str())));
+        }
+        if let Some(path) = &self.body_file {
+            let data = std::fs::read(path).map_err(|e| format!("Failed to read body_file '{}': {}", path, e))?;
+            return Ok(Some(RequestBody::raw(data)));
+        }
+        Ok(self.json.as_ref().map(RequestBody::json))
+    }
+
+    /// Whether `alert` applies to this target.
+    pub fn notifies(&self, alert: &Alert) -> bool {
+        self.alerts.as_ref().is_none_or(|names| names.contains(&alert.name))
+    }
+}
+
+/// Why a config document couldn't be turned into a `Config`.
+#[derive(Debug)]
+pub enum LoadError {
+    Yaml(serde_yaml::Error),
+    /// Unresolvable `${...}` references or group problems
+    Problems(Vec<Problem>),
+}
+
+impl Settings {
+    /// Health thresholds for `target`, with its overrides applied.
+    pub fn health_for(&self, target: &Target) -> HealthThresholds {
+        match &target.health {
+            Some(overrides) => self.health.with_overrides(overrides),
+            None => self.health.clone(),
         }
     }
+
+    pub fn max_consecutive_failures_for(&self, target: &Target) -> u32 {
+        target.max_consecutive_failures.unwrap_or(self.max_consecutive_failures)
+    }
 }
 
 impl Config {
+    /// Loads a config (see `parse`), rejecting it if any semantic check finds
+    /// an error.
     pub fn from_file(path: &str) -> Result<Self, Box<dyn std::error::Error>> {
         let content = std::fs::read_to_string(path)?;
-        let config: Config = serde_yaml::from_str(&content)?;
-        Ok(config)
+        let errors = match Self::parse(&content) {
+            Ok(config) => {
+                let errors: Vec<Problem> = validate::check(&config)
+                    .into_iter()
+                    .filter(|p| p.severity == Severity::Error)
+                    .collect();
+                if errors.is_empty() {
+                    return Ok(config);
+                }
+                errors
+            }
+            Err(LoadError::Yaml(e)) => return Err(e.into()),
+            Err(LoadError::Problems(problems)) => problems,
+        };
+
+        let lines = LineIndex::parse(&content);
+        Err(format!(
+            "{} configuration error(s), run 'httpping validate {}' to list them all. First: {}",
+            errors.len(),
+            path,
+            errors[0].display(path, &lines)
+        )
+        .into())
+    }
+
+    /// Parses a config document, expanding `${...}` references (see
+    /// `interpolate`) and applying groups and setting defaults to targets
+    /// (see `inherit`).
+    pub fn parse(content: &str) -> Result<Self, LoadError> {
+        let mut value: serde_yaml::Value = serde_yaml::from_str(content).map_err(LoadError::Yaml)?;
+
+        let mut problems = interpolate::expand(&mut value);
+        problems.extend(inherit::resolve(&mut value));
+        if !problems.is_empty() {
+            return Err(LoadError::Problems(problems));
+        }
+
+        // The resolved document has no positions, so errors come from the raw
+        // text where it fails the same way
+        serde_yaml::from_value(value).map_err(|e| match serde_yaml::from_str::<Config>(content) {
+            Err(raw) if raw.location().is_some() => LoadError::Yaml(raw),
+            _ => LoadError::Yaml(e),
+        })
     }
 
     pub fn example() -> Self {
@@ -125,22 +527,38 @@ impl Config {
                 Target {
                     name: "Production API".to_string(),
                     url: "https://api.example.com/health".to_string(),
-                    method: "GET".to_string(),
+                    group: None,
+                    method: Method::GET,
                     headers: HashMap::new(),
+                    body: None,
+                    body_file: None,
+                    json: None,
                     expected_status: vec![200],
                     expected_content: Some("\"status\":\"ok\"".to_string()),
+                    assertions: vec![Assertion::NotContains("maintenance mode".to_string())],
                     timeout_seconds: 5.0,
                     interval_seconds: 30.0,
+                    max_consecutive_failures: None,
+                    health: None,
+                    alerts: None,
                 },
                 Target {
                     name: "Main Website".to_string(),
                     url: "https://example.com".to_string(),
-                    method: "GET".to_string(),
+                    group: None,
+                    method: Method::GET,
                     headers: HashMap::new(),
+                    body: None,
+                    body_file: None,
+                    json: None,
                     expected_status: vec![200, 301, 302],
                     expected_content: None,
+                    assertions: vec![],
                     timeout_seconds: 10.0,
                     interval_seconds: 60.0,
+                    max_consecutive_failures: None,
+                    health: None,
+                    alerts: None,
                 },
             ],
             settings: Settings::default(),
@@ -156,6 +574,7 @@ impl Config {
                     cooldown_minutes: 30,
                 },
             ],
+            groups: HashMap::new(),
         }
     }
 }
\ No newline at end of file
@@ -0,0 +1,210 @@
+use serde::{Deserialize, Serialize};
+use std::error::Error as StdError;
+use std::fmt;
+use std::io;
+
+/// Why a check or ping failed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorKind {
+    DnsFailure,
+    ConnectionRefused,
+    ConnectionReset,
+    Timeout,
+    TlsError,
+    CertInvalid,
+    /// The response status wasn't one of the expected ones
+    HttpStatus,
+    /// The response failed a content, header or JSON assertion
+    ContentMismatch,
+    BodyReadError,
+    RedirectLoop,
+    /// Anything else, e.g. an unreadable `body_file` or unreachable network
+    Other,
+}
+
+impl ErrorKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ErrorKind::DnsFailure => "dns_failure",
+            ErrorKind::ConnectionRefused => "connection_refused",
+            ErrorKind::ConnectionReset => "connection_reset",
+            ErrorKind::Timeout => "timeout",
+            ErrorKind::TlsError => "tls_error",
+            ErrorKind::CertInvalid => "cert_invalid",
+            ErrorKind::HttpStatus => "http_status",
+            ErrorKind::ContentMismatch => "content_mismatch",
+            ErrorKind::BodyReadError => "body_read_error",
+            ErrorKind::RedirectLoop => "redirect_loop",
+            ErrorKind::Other => "other",
+        }
+    }
+
+    /// Classifies a failed request by walking the reqwest error's source chain.
+    pub fn from_reqwest(err: &reqwest::Error) -> Self {
+        if err.is_timeout() {
+            return ErrorKind::Timeout;
+        }
+        if err.is_redirect() {
+            return ErrorKind::RedirectLoop;
+        }
+
+        let mut source: Option<&(dyn StdError + 'static)> = Some(err);
+        while let Some(e) = source {
+            if let Some(kind) = Self::from_error(e) {
+                return kind;
+            }
+            source = match e.downcast_ref::<io::Error>() {
+                // io::Error::source() skips the error it wraps, so step into it explicitly
+                Some(io_err) => io_err
+                    .get_ref()
+                    .map(|inner| inner as &(dyn StdError + 'static)),
+                None => e.source(),
+            };
+        }
+
+        if err.is_body() || err.is_decode() {
+            ErrorKind::BodyReadError
+        } else {
+            ErrorKind::Other
+        }
+    }
+
+    fn from_error(e: &(dyn StdError + 'static)) -> Option<Self> {
+        if let Some(tls) = e.downcast_ref::<rustls::Error>() {
+            return Some(match tls {
+                rustls::Error::InvalidCertificate(_) => ErrorKind::CertInvalid,
+                _ => ErrorKind::TlsError,
+            });
+        }
+        if let Some(io_err) = e.downcast_ref::<io::Error>() {
+            match io_err.kind() {
+                io::ErrorKind::ConnectionRefused => return Some(ErrorKind::ConnectionRefused),
+                io::ErrorKind::ConnectionReset
+                | io::ErrorKind::ConnectionAborted
+                | io::ErrorKind::BrokenPipe => return Some(ErrorKind::ConnectionReset),
+                io::ErrorKind::TimedOut => return Some(ErrorKind::Timeout),
+                _ => {}
+            }
+        }
+        // hyper reports resolver failures only through the message
+        if e.to_string().starts_with("dns error") {
+            return Some(ErrorKind::DnsFailure);
+        }
+        None
+    }
+}
+
+impl fmt::Display for ErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tls::tests::serve_cert;
+    use std::time::Duration;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    /// Answers every connection with `response` once the client has sent
+    /// something, whatever that was.
+    async fn serve_raw(response: &'static [u8]) -> u16 {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                tokio::spawn(async move {
+                    let mut buf = [0; 4096];
+                    let _ = stream.read(&mut buf).await;
+                    let _ = stream.write_all(response).await;
+                });
+            }
+        });
+        port
+    }
+
+    async fn kind_of(url: &str) -> ErrorKind {
+        let client = reqwest::Client::builder()
+            .no_proxy()
+            .timeout(Duration::from_secs(2))
+            .build()
+            .unwrap();
+        let err = client
+            .get(url)
+            .send()
+            .await
+            .expect_err("request should fail");
+        ErrorKind::from_reqwest(&err)
+    }
+
+    #[tokio::test]
+    async fn closed_port_is_connection_refused() {
+        let port = {
+            let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+            listener.local_addr().unwrap().port()
+        };
+        assert_eq!(
+            kind_of(&format!("http://127.0.0.1:{}/", port)).await,
+            ErrorKind::ConnectionRefused
+        );
+    }
+
+    #[tokio::test]
+    async fn unresolvable_host_is_dns_failure() {
+        assert_eq!(
+            kind_of("http://httpping-test.invalid/").await,
+            ErrorKind::DnsFailure
+        );
+    }
+
+    #[tokio::test]
+    async fn plain_http_spoken_to_over_tls_is_tls_error() {
+        let port = serve_raw(b"HTTP/1.1 400 Bad Request\r\nContent-Length: 0\r\n\r\n").await;
+        assert_eq!(
+            kind_of(&format!("https://127.0.0.1:{}/", port)).await,
+            ErrorKind::TlsError
+        );
+    }
+
+    #[tokio::test]
+    async fn self_signed_certificate_is_cert_invalid() {
+        let port = serve_cert(30).await;
+        assert_eq!(
+            kind_of(&format!("https://localhost:{}/", port)).await,
+            ErrorKind::CertInvalid
+        );
+    }
+
+    #[tokio::test]
+    async fn redirect_loop_is_redirect_loop() {
+        let port = serve_raw(
+            b"HTTP/1.1 302 Found\r\nLocation: /\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+        )
+        .await;
+        assert_eq!(
+            kind_of(&format!("http://127.0.0.1:{}/", port)).await,
+            ErrorKind::RedirectLoop
+        );
+    }
+
+    #[tokio::test]
+    async fn unanswered_request_is_timeout() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let client = reqwest::Client::builder()
+            .no_proxy()
+            .timeout(Duration::from_millis(200))
+            .build()
+            .unwrap();
+        let err = client
+            .get(format!("http://127.0.0.1:{}/", port))
+            .send()
+            .await
+            .unwrap_err();
+        assert_eq!(ErrorKind::from_reqwest(&err), ErrorKind::Timeout);
+        drop(listener);
+    }
+}
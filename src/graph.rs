@@ -0,0 +1,144 @@
+use crate::output;
+use crate::{PingResult, PingStatistics};
+use colored::*;
+use console::Term;
+use std::collections::VecDeque;
+use std::io;
+use std::time::Duration;
+
+/// Rows of bars; each row holds eight levels of block characters.
+const HEIGHT: usize = 8;
+/// Width of the y-axis labels, e.g. `1234ms`.
+const LABEL_WIDTH: usize = 7;
+/// More than any terminal is wide; older samples scroll off the left.
+const MAX_SAMPLES: usize = 1000;
+const BLOCKS: [char; 9] = [' ', '▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// Rolling bar chart of response times for single-URL ping, redrawn in place
+/// after every request like `gping`. Failed requests show as a red `×`.
+pub struct LatencyGraph {
+    term: Term,
+    samples: VecDeque<(Duration, bool)>,
+    /// Lines drawn last time, to be overwritten by the next draw
+    drawn: usize,
+}
+
+impl LatencyGraph {
+    /// `None` when stdout isn't a terminal, since the graph can't redraw there.
+    pub fn new() -> Option<Self> {
+        let term = Term::stdout();
+        term.is_term().then(|| Self {
+            term,
+            samples: VecDeque::new(),
+            drawn: 0,
+        })
+    }
+
+    pub fn update(&mut self, result: &PingResult, stats: &PingStatistics) -> io::Result<()> {
+        if self.samples.len() >= MAX_SAMPLES {
+            self.samples.pop_front();
+        }
+        self.samples
+            .push_back((result.response_time, result.success));
+
+        let (_, columns) = self.term.size();
+        let mut lines = self.chart(columns as usize);
+        lines.push(
+            console::truncate_str(&status_line(result, stats), columns as usize, "…").into_owned(),
+        );
+
+        self.term.clear_last_lines(self.drawn)?;
+        for line in &lines {
+            self.term.write_line(line)?;
+        }
+        self.drawn = lines.len();
+        Ok(())
+    }
+
+    /// The bars for as many recent samples as fit, scaled to the slowest
+    /// successful one among them.
+    fn chart(&self, columns: usize) -> Vec<String> {
+        let width = columns.saturating_sub(LABEL_WIDTH + 2).max(1);
+        let shown: Vec<&(Duration, bool)> = self
+            .samples
+            .iter()
+            .skip(self.samples.len().saturating_sub(width))
+            .collect();
+        let max = shown
+            .iter()
+            .filter(|(_, success)| *success)
+            .map(|(time, _)| *time)
+            .max()
+            .unwrap_or_default();
+
+        (0..HEIGHT)
+            .rev()
+            .map(|row| {
+                let (label, axis) = match row {
+                    _ if row == HEIGHT - 1 => (format!("{}ms", max.as_millis()), '┤'),
+                    0 => ("0ms".to_string(), '┼'),
+                    _ => (String::new(), '│'),
+                };
+                let mut line = format!("{:>width$} {}", label, axis, width = LABEL_WIDTH);
+                for (response_time, success) in &shown {
+                    line.push_str(&bar_cell(*response_time, *success, max, row));
+                }
+                line
+            })
+            .collect()
+    }
+}
+
+/// The part of a sample's bar that falls in `row`, counted from the bottom.
+fn bar_cell(response_time: Duration, success: bool, max: Duration, row: usize) -> String {
+    if !success {
+        return if row == 0 {
+            "×".red().to_string()
+        } else {
+            " ".to_string()
+        };
+    }
+
+    let level = if max.is_zero() {
+        0.0
+    } else {
+        response_time.as_secs_f64() / max.as_secs_f64()
+    };
+    let eighths = (level * (HEIGHT * 8) as f64).round() as usize;
+    let mut filled = eighths.saturating_sub(row * 8).min(8);
+    // Keep even the fastest responses visible
+    if row == 0 && filled == 0 {
+        filled = 1;
+    }
+    if filled == 0 {
+        return " ".to_string();
+    }
+
+    let cell = BLOCKS[filled].to_string();
+    match response_time.as_millis() {
+        0..=50 => cell.green().to_string(),
+        51..=200 => cell.yellow().to_string(),
+        _ => cell.red().to_string(),
+    }
+}
+
+fn status_line(result: &PingResult, stats: &PingStatistics) -> String {
+    let status = result.status_code.map_or(
+        output::failure_label(result.error_kind).to_string(),
+        |code| code.to_string(),
+    );
+    let mut line = format!(
+        "seq={} status={} time={}ms | min/avg/max = {}/{}/{} ms | loss {:.1}%",
+        result.sequence,
+        status,
+        result.response_time.as_millis(),
+        stats.min_response_time.as_millis(),
+        stats.avg_response_time.as_millis(),
+        stats.max_response_time.as_millis(),
+        100.0 - stats.success_rate
+    );
+    if let Some(kind) = result.error_kind {
+        line.push_str(&format!(" | {}", kind));
+    }
+    line
+}
@@ -0,0 +1,228 @@
+use crate::validate::Problem;
+use serde_yaml::{Mapping, Value};
+
+/// Fills in each target from its `group` and then from the settings'
+/// `default_interval`/`default_timeout`, in place. Fields set on the target
+/// win, except that `headers` are merged key by key and the group's
+/// `assertions` come before the target's own.
+pub fn resolve(document: &mut Value) -> Vec<Problem> {
+    let mut problems = Vec::new();
+    let groups = document.get("groups").cloned().unwrap_or(Value::Null);
+    if let Value::Mapping(groups) = &groups {
+        check_groups(groups, &mut problems);
+    }
+    let defaults = settings_defaults(document);
+
+    let Some(Value::Sequence(targets)) = document.get_mut("targets") else {
+        return problems;
+    };
+    for (i, target) in targets.iter_mut().enumerate() {
+        let Value::Mapping(target) = target else {
+            continue;
+        };
+
+        if let Some(name) = target.get("group").filter(|name| !name.is_null()) {
+            let group = name.as_str().and_then(|name| groups.get(name));
+            match group {
+                Some(Value::Mapping(group)) => inherit(target, group),
+                // Malformed groups were already reported by check_groups
+                Some(_) => {}
+                None => problems.push(Problem::error(
+                    format!("targets[{}].group", i),
+                    format!("unknown group {}", describe(name)),
+                )),
+            }
+        }
+
+        for (key, value) in &defaults {
+            if !target.contains_key(*key) {
+                target.insert(Value::from(*key), value.clone());
+            }
+        }
+    }
+    problems
+}
+
+fn check_groups(groups: &Mapping, problems: &mut Vec<Problem>) {
+    for (name, group) in groups {
+        let path = format!("groups.{}", name.as_str().unwrap_or_default());
+        match group {
+            Value::Mapping(group) => {
+                for key in ["name", "url", "group"] {
+                    if group.contains_key(key) {
+                        problems.push(Problem::error(
+                            format!("{}.{}", path, key),
+                            format!("'{}' can't be set on a group", key),
+                        ));
+                    }
+                }
+            }
+            _ => problems.push(Problem::error(
+                path,
+                "group must be a mapping of target fields",
+            )),
+        }
+    }
+}
+
+/// Target fields that fall back to a setting, and the setting's value.
+fn settings_defaults(document: &Value) -> Vec<(&'static str, Value)> {
+    let settings = document.get("settings");
+    [
+        ("timeout_seconds", "default_timeout"),
+        ("interval_seconds", "default_interval"),
+    ]
+    .into_iter()
+    .filter_map(|(field, setting)| Some((field, settings?.get(setting)?.clone())))
+    .collect()
+}
+
+fn inherit(target: &mut Mapping, group: &Mapping) {
+    for (key, value) in group {
+        match (key.as_str(), target.get_mut(key)) {
+            (Some("headers"), Some(Value::Mapping(headers))) => {
+                if let Value::Mapping(shared) = value {
+                    for (name, header) in shared {
+                        if !headers.contains_key(name) {
+                            headers.insert(name.clone(), header.clone());
+                        }
+                    }
+                }
+            }
+            (Some("assertions"), Some(Value::Sequence(assertions))) => {
+                if let Value::Sequence(shared) = value {
+                    let own = std::mem::replace(assertions, shared.clone());
+                    assertions.extend(own);
+                }
+            }
+            (_, None) => {
+                target.insert(key.clone(), value.clone());
+            }
+            _ => {}
+        }
+    }
+}
+
+fn describe(value: &Value) -> String {
+    match value {
+        Value::String(s) => format!("'{}'", s),
+        other => serde_yaml::to_string(other)
+            .unwrap_or_default()
+            .trim()
+            .to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn resolved(document: &str) -> (Value, Vec<Problem>) {
+        let mut value: Value = serde_yaml::from_str(document).unwrap();
+        let problems = resolve(&mut value);
+        (value, problems)
+    }
+
+    fn yaml(text: &str) -> Value {
+        serde_yaml::from_str(text).unwrap()
+    }
+
+    #[test]
+    fn merges_target_fields() {
+        let document = "
+settings: {default_timeout: 5, default_interval: 60}
+groups:
+  api:
+    headers: {Accept: application/json, X-Team: core}
+    assertions: [!contains ok]
+    timeout_seconds: 2
+    expected_status: [200]
+    body: group body
+targets:
+  - name: a
+    url: http://a
+    group: api
+    headers: {X-Team: edge}
+    assertions: [!not_contains error]
+    interval_seconds: 10
+    expected_status: [204]
+  - name: b
+    url: http://b
+    group: api
+    timeout_seconds: 9
+    body: null
+  - name: c
+    url: http://c
+    group: null
+";
+        let (value, problems) = resolved(document);
+        assert!(problems.is_empty(), "{:?}", problems);
+        let targets = &value["targets"];
+
+        // (target, field, expected value)
+        let cases = [
+            // Headers merge key by key, the target's own value winning
+            (0, "headers", "{X-Team: edge, Accept: application/json}"),
+            // Group assertions run first
+            (0, "assertions", "[!contains ok, !not_contains error]"),
+            // Other fields set on the target win over the group...
+            (0, "expected_status", "[204]"),
+            (0, "interval_seconds", "10"),
+            // ...the group wins over the settings defaults...
+            (0, "timeout_seconds", "2"),
+            (0, "body", "group body"),
+            // ...and the settings defaults fill what's left
+            (1, "interval_seconds", "60"),
+            (1, "timeout_seconds", "9"),
+            // A group's headers and assertions apply as they are when the target has none
+            (1, "headers", "{Accept: application/json, X-Team: core}"),
+            (1, "assertions", "[!contains ok]"),
+            // An explicit null is a value of its own, not a gap for the group to fill
+            (1, "body", "null"),
+            // `group: null` means no group
+            (2, "timeout_seconds", "5"),
+            (2, "headers", "null"),
+        ];
+        for (i, field, expected) in cases {
+            let actual = targets[i].get(field).cloned().unwrap_or(Value::Null);
+            assert_eq!(actual, yaml(expected), "targets[{}].{}", i, field);
+        }
+    }
+
+    #[test]
+    fn without_settings_targets_are_left_to_serde_defaults() {
+        let (value, problems) = resolved("targets:\n  - {name: a, url: http://a}\n");
+        assert!(problems.is_empty());
+        assert_eq!(value["targets"][0], yaml("{name: a, url: http://a}"));
+    }
+
+    #[test]
+    fn reports_group_problems() {
+        let document = "
+groups:
+  bad: [1, 2]
+  named: {name: x, url: http://x}
+targets:
+  - {name: a, url: http://a, group: missing}
+  - {name: b, url: http://b, group: 3}
+";
+        let (_, problems) = resolved(document);
+        let mut problems: Vec<(String, String)> =
+            problems.into_iter().map(|p| (p.path, p.message)).collect();
+        problems.sort();
+
+        // (path, message)
+        let expected = [
+            ("groups.bad", "group must be a mapping of target fields"),
+            ("groups.named.name", "'name' can't be set on a group"),
+            ("groups.named.url", "'url' can't be set on a group"),
+            ("targets[0].group", "unknown group 'missing'"),
+            ("targets[1].group", "unknown group 3"),
+        ];
+        let expected: Vec<(String, String)> = expected
+            .iter()
+            .map(|(path, message)| (path.to_string(), message.to_string()))
+            .collect();
+        assert_eq!(problems, expected);
+    }
+}
@@ -0,0 +1,241 @@
+use crate::validate::Problem;
+use serde_yaml::Value;
+
+/// Expands `${VAR}`, `${VAR:-default}` and `${file:/path}` references in every
+/// string value of a parsed config, in place. `$${` is a literal `${`.
+/// Returns one problem per unresolvable reference.
+pub fn expand(value: &mut Value) -> Vec<Problem> {
+    let mut problems = Vec::new();
+    expand_value(value, String::new(), &mut problems);
+    problems
+}
+
+fn expand_value(value: &mut Value, path: String, problems: &mut Vec<Problem>) {
+    match value {
+        Value::String(s) => match expand_str(s) {
+            Ok(expanded) => *s = expanded,
+            Err(errors) => {
+                for e in errors {
+                    problems.push(Problem::error(path.clone(), e));
+                }
+            }
+        },
+        Value::Sequence(items) => {
+            for (i, item) in items.iter_mut().enumerate() {
+                expand_value(item, format!("{}[{}]", path, i), problems);
+            }
+        }
+        Value::Mapping(map) => {
+            for (key, item) in map.iter_mut() {
+                let key = match key {
+                    Value::String(key) => key.clone(),
+                    other => serde_yaml::to_string(other)
+                        .unwrap_or_default()
+                        .trim()
+                        .to_string(),
+                };
+                let child = if path.is_empty() {
+                    key
+                } else {
+                    format!("{}.{}", path, key)
+                };
+                expand_value(item, child, problems);
+            }
+        }
+        // Externally tagged enums such as `!contains "${TEXT}"`
+        Value::Tagged(tagged) => expand_value(&mut tagged.value, path, problems),
+        _ => {}
+    }
+}
+
+fn expand_str(input: &str) -> Result<String, Vec<String>> {
+    if !input.contains("${") {
+        return Ok(input.to_string());
+    }
+
+    let mut out = String::with_capacity(input.len());
+    let mut errors = Vec::new();
+    let mut rest = input;
+    while let Some(start) = rest.find("${") {
+        if rest[..start].ends_with('$') {
+            out.push_str(&rest[..start - 1]);
+            out.push_str("${");
+            rest = &rest[start + 2..];
+            continue;
+        }
+        out.push_str(&rest[..start]);
+
+        let Some(len) = rest[start + 2..].find('}') else {
+            errors.push(format!("unterminated '${{' in '{}'", input));
+            return Err(errors);
+        };
+        let reference = &rest[start + 2..start + 2 + len];
+        match resolve(reference) {
+            Ok(value) => out.push_str(&value),
+            Err(e) => errors.push(e),
+        }
+        rest = &rest[start + 2 + len + 1..];
+    }
+    out.push_str(rest);
+
+    if errors.is_empty() {
+        Ok(out)
+    } else {
+        Err(errors)
+    }
+}
+
+fn resolve(reference: &str) -> Result<String, String> {
+    if let Some(path) = reference.strip_prefix("file:") {
+        return std::fs::read_to_string(path)
+            .map(|content| content.trim_end_matches(['\r', '\n']).to_string())
+            .map_err(|e| format!("failed to read secret file '{}': {}", path, e));
+    }
+
+    let (name, default) = match reference.split_once(":-") {
+        Some((name, default)) => (name, Some(default)),
+        None => (reference, None),
+    };
+    if name.is_empty() {
+        return Err("empty variable name in '${}'".to_string());
+    }
+
+    // Like the shell, `:-` also covers variables that are set but empty
+    match (std::env::var(name), default) {
+        (Ok(value), Some(default)) if value.is_empty() => Ok(default.to_string()),
+        (Ok(value), _) => Ok(value),
+        (Err(_), Some(default)) => Ok(default.to_string()),
+        (Err(std::env::VarError::NotPresent), None) => {
+            Err(format!("environment variable '{}' is not set", name))
+        }
+        (Err(std::env::VarError::NotUnicode(_)), None) => Err(format!(
+            "environment variable '{}' is not valid UTF-8",
+            name
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn leaves_plain_strings_alone() {
+        assert_eq!(
+            expand_str("https://example.com/$path").unwrap(),
+            "https://example.com/$path"
+        );
+    }
+
+    #[test]
+    fn expands_variables() {
+        std::env::set_var("HTTPPING_TEST_HOST", "api.example.com");
+        assert_eq!(
+            expand_str("https://${HTTPPING_TEST_HOST}/health").unwrap(),
+            "https://api.example.com/health"
+        );
+    }
+
+    #[test]
+    fn double_dollar_is_a_literal_reference() {
+        std::env::set_var("HTTPPING_TEST_ESCAPED", "expanded");
+        assert_eq!(
+            expand_str("$${HTTPPING_TEST_ESCAPED} ${HTTPPING_TEST_ESCAPED}").unwrap(),
+            "${HTTPPING_TEST_ESCAPED} expanded"
+        );
+    }
+
+    #[test]
+    fn default_covers_unset_and_empty_variables() {
+        std::env::remove_var("HTTPPING_TEST_UNSET");
+        std::env::set_var("HTTPPING_TEST_EMPTY", "");
+        std::env::set_var("HTTPPING_TEST_SET", "value");
+        assert_eq!(
+            expand_str("${HTTPPING_TEST_UNSET:-fallback}").unwrap(),
+            "fallback"
+        );
+        assert_eq!(
+            expand_str("${HTTPPING_TEST_EMPTY:-fallback}").unwrap(),
+            "fallback"
+        );
+        assert_eq!(
+            expand_str("${HTTPPING_TEST_SET:-fallback}").unwrap(),
+            "value"
+        );
+        assert_eq!(expand_str("${HTTPPING_TEST_UNSET:-}").unwrap(), "");
+    }
+
+    #[test]
+    fn empty_variable_without_default_is_kept() {
+        std::env::set_var("HTTPPING_TEST_BLANK", "");
+        assert_eq!(expand_str("[${HTTPPING_TEST_BLANK}]").unwrap(), "[]");
+    }
+
+    #[test]
+    fn reports_every_unresolvable_reference() {
+        std::env::remove_var("HTTPPING_TEST_MISSING_A");
+        std::env::remove_var("HTTPPING_TEST_MISSING_B");
+        let errors =
+            expand_str("${HTTPPING_TEST_MISSING_A}/${HTTPPING_TEST_MISSING_B}").unwrap_err();
+        assert_eq!(
+            errors,
+            [
+                "environment variable 'HTTPPING_TEST_MISSING_A' is not set",
+                "environment variable 'HTTPPING_TEST_MISSING_B' is not set",
+            ]
+        );
+        assert_eq!(
+            expand_str("${}").unwrap_err(),
+            ["empty variable name in '${}'"]
+        );
+    }
+
+    #[test]
+    fn rejects_unterminated_references() {
+        assert_eq!(
+            expand_str("token=${TOKEN").unwrap_err(),
+            ["unterminated '${' in 'token=${TOKEN'"]
+        );
+    }
+
+    #[test]
+    fn reads_secret_files_without_trailing_newlines() {
+        let path = std::env::temp_dir().join(format!("httpping-secret-{}", std::process::id()));
+        std::fs::write(&path, "  s3cret \r\n\n").unwrap();
+        let reference = format!("Bearer ${{file:{}}}", path.display());
+        let expanded = expand_str(&reference);
+        std::fs::remove_file(&path).unwrap();
+        // Only line endings are trimmed; other whitespace is part of the secret
+        assert_eq!(expanded.unwrap(), "Bearer   s3cret ");
+    }
+
+    #[test]
+    fn reports_unreadable_secret_files() {
+        let errors = expand_str("${file:/nonexistent/httpping-secret}").unwrap_err();
+        assert!(
+            errors[0].starts_with("failed to read secret file '/nonexistent/httpping-secret'"),
+            "{:?}",
+            errors
+        );
+    }
+
+    #[test]
+    fn expands_nested_values_and_tags() {
+        std::env::set_var("HTTPPING_TEST_NESTED", "ok");
+        let mut value: Value = serde_yaml::from_str(
+            "targets:\n  - url: http://${HTTPPING_TEST_NESTED}\n    assertions:\n      - !contains \"${HTTPPING_TEST_NESTED}\"\n",
+        )
+        .unwrap();
+        assert!(expand(&mut value).is_empty());
+        assert_eq!(value["targets"][0]["url"], Value::from("http://ok"));
+        let tagged: Value = serde_yaml::from_str("!contains ok").unwrap();
+        assert_eq!(value["targets"][0]["assertions"][0], tagged);
+
+        std::env::remove_var("HTTPPING_TEST_MISSING_C");
+        let mut value: Value =
+            serde_yaml::from_str("targets:\n  - url: ${HTTPPING_TEST_MISSING_C}\n").unwrap();
+        let problems = expand(&mut value);
+        assert_eq!(problems.len(), 1);
+        assert_eq!(problems[0].path, "targets[0].url");
+    }
+}
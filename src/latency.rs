@@ -0,0 +1,60 @@
+use hdrhistogram::Histogram;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// Tail latency summary, in the spirit of `ping`'s min/avg/max/mdev line.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LatencySummary {
+    pub p50: Duration,
+    pub p90: Duration,
+    pub p95: Duration,
+    pub p99: Duration,
+    pub p99_9: Duration,
+    pub stddev: Duration,
+}
+
+/// Response time histogram with microsecond resolution and 3 significant
+/// digits, covering 1µs up to one hour.
+#[derive(Debug, Clone)]
+pub struct LatencyHistogram {
+    histogram: Histogram<u64>,
+}
+
+impl LatencyHistogram {
+    pub fn new() -> Self {
+        let histogram = Histogram::new_with_bounds(1, 3_600_000_000, 3)
+            .expect("static histogram bounds are valid");
+        Self { histogram }
+    }
+
+    pub fn record(&mut self, latency: Duration) {
+        let micros = (latency.as_micros() as u64).max(1);
+        self.histogram.saturating_record(micros);
+    }
+
+    pub fn mean(&self) -> Duration {
+        micros(self.histogram.mean())
+    }
+
+    pub fn summary(&self) -> LatencySummary {
+        let at = |q: f64| Duration::from_micros(self.histogram.value_at_quantile(q));
+        LatencySummary {
+            p50: at(0.50),
+            p90: at(0.90),
+            p95: at(0.95),
+            p99: at(0.99),
+            p99_9: at(0.999),
+            stddev: micros(self.histogram.stdev()),
+        }
+    }
+}
+
+impl Default for LatencyHistogram {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn micros(value: f64) -> Duration {
+    Duration::from_secs_f64(value.max(0.0) / 1_000_000.0)
+}
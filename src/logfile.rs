@@ -0,0 +1,97 @@
+use crate::config::{LogRotation, OutputFormat};
+use crate::monitor::HealthCheck;
+use crate::output;
+use chrono::{DateTime, Utc};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::PathBuf;
+
+/// Appends health checks to `Settings.log_file`, rotating it by size and/or age.
+pub struct CheckLog {
+    path: PathBuf,
+    format: OutputFormat,
+    rotation: LogRotation,
+    file: File,
+    size: u64,
+    opened_at: DateTime<Utc>,
+}
+
+impl CheckLog {
+    pub fn open(path: &str, format: OutputFormat, rotation: LogRotation) -> io::Result<Self> {
+        let path = PathBuf::from(path);
+        let (file, size) = Self::open_file(&path, &format)?;
+        Ok(Self {
+            path,
+            format,
+            rotation,
+            file,
+            size,
+            opened_at: Utc::now(),
+        })
+    }
+
+    fn open_file(path: &PathBuf, format: &OutputFormat) -> io::Result<(File, u64)> {
+        let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+        let mut size = file.metadata()?.len();
+        if size == 0 {
+            if let OutputFormat::Csv = format {
+                writeln!(file, "{}", output::CSV_HEADER)?;
+                size = output::CSV_HEADER.len() as u64 + 1;
+            }
+        }
+        Ok((file, size))
+    }
+
+    pub fn write(&mut self, check: &HealthCheck) -> io::Result<()> {
+        if self.should_rotate() {
+            self.rotate()?;
+        }
+
+        let line = match self.format {
+            OutputFormat::Pretty => output::check_to_text(check),
+            OutputFormat::Csv => output::check_to_csv(check),
+            // The exposition format describes current state, not events, so
+            // the log keeps a JSON history instead
+            OutputFormat::Json | OutputFormat::Prometheus => output::check_to_json(check),
+        };
+        writeln!(self.file, "{}", line)?;
+        self.size += line.len() as u64 + 1;
+        Ok(())
+    }
+
+    fn should_rotate(&self) -> bool {
+        let too_big = self
+            .rotation
+            .max_size_mb
+            .is_some_and(|mb| self.size >= mb * 1024 * 1024);
+        let too_old = self.rotation.max_age_hours.is_some_and(|hours| {
+            Utc::now().signed_duration_since(self.opened_at)
+                >= chrono::Duration::hours(hours as i64)
+        });
+        too_big || too_old
+    }
+
+    fn rotate(&mut self) -> io::Result<()> {
+        let keep = self.rotation.keep;
+        let rotated = |n: u32| PathBuf::from(format!("{}.{}", self.path.display(), n));
+
+        if keep == 0 {
+            fs::remove_file(&self.path)?;
+        } else {
+            let _ = fs::remove_file(rotated(keep));
+            for n in (1..keep).rev() {
+                let from = rotated(n);
+                if from.exists() {
+                    fs::rename(&from, rotated(n + 1))?;
+                }
+            }
+            fs::rename(&self.path, rotated(1))?;
+        }
+
+        let (file, size) = Self::open_file(&self.path, &self.format)?;
+        self.file = file;
+        self.size = size;
+        self.opened_at = Utc::now();
+        Ok(())
+    }
+}
@@ -1,14 +1,32 @@
+mod assertions;
 mod config;
+mod errors;
+mod graph;
+mod inherit;
+mod interpolate;
+mod latency;
+mod logfile;
+mod metrics;
 mod monitor;
+mod output;
+mod status;
+mod timing;
+mod tls;
+mod tui;
+mod validate;
 
 use clap::{Parser, Subcommand};
 use colored::*;
-use config::{Config, Target};
+use config::{Config, RequestBody};
+use graph::LatencyGraph;
+use errors::ErrorKind;
+use latency::{LatencyHistogram, LatencySummary};
 use monitor::Monitor;
 use rand::seq::SliceRandom;
+use timing::{ConnectionProbe, PhaseTimings};
 use reqwest::{Client, Method};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
@@ -35,12 +53,21 @@ struct Args {
     #[arg(short = 't', long = "timeout", help = "Request timeout in seconds", default_value = "10.0")]
     timeout: f64,
 
-    #[arg(short = 'm', long = "method", help = "HTTP method", default_value = "GET")]
-    method: String,
+    #[arg(short = 'm', long = "method", help = "HTTP method", default_value = "GET", value_parser = config::parse_method)]
+    method: Method,
 
     #[arg(short = 'H', long = "header", help = "Custom headers (can be used multiple times)")]
     headers: Vec<String>,
 
+    #[arg(short = 'd', long = "data", help = "Request body, sent as form data", conflicts_with_all = ["data_file", "json_body"])]
+    data: Option<String>,
+
+    #[arg(long = "data-file", help = "Read the request body from a file", conflicts_with = "json_body")]
+    data_file: Option<String>,
+
+    #[arg(long = "json-body", help = "JSON request body (sets Content-Type: application/json)")]
+    json_body: Option<String>,
+
     #[arg(short = 'u', long = "user-agent", help = "Custom User-Agent")]
     user_agent: Option<String>,
 
@@ -58,6 +85,9 @@ struct Args {
 
     #[arg(long = "json", help = "JSON output format")]
     json: bool,
+
+    #[arg(short = 'g', long = "graph", help = "Draw a live latency graph instead of one line per request", conflicts_with_all = ["json", "stats_only"])]
+    graph: bool,
 }
 
 #[derive(Subcommand, Debug)]
@@ -66,12 +96,23 @@ enum Commands {
     Monitor {
         #[arg(short, long, help = "Path to configuration file")]
         config: String,
+
+        #[arg(long, help = "Reload the configuration when the file changes (SIGHUP always reloads)")]
+        watch: bool,
+
+        #[arg(long, help = "Show a full-screen dashboard instead of printing each check")]
+        tui: bool,
     },
     /// Generate example configuration file
     Init {
         #[arg(short, long, help = "Output path for config file", default_value = "httpping.yml")]
         output: String,
     },
+    /// Check configuration files for problems without running them
+    Validate {
+        #[arg(required = true, help = "Configuration files to check")]
+        files: Vec<String>,
+    },
     /// Single URL ping (same as legacy mode)
     Ping {
         #[arg(help = "URL to ping")]
@@ -96,7 +137,10 @@ struct PingResult {
     response_time: Duration,
     success: bool,
     error: Option<String>,
+    error_kind: Option<ErrorKind>,
     timestamp: chrono::DateTime<chrono::Utc>,
+    #[serde(flatten)]
+    timings: PhaseTimings,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -108,6 +152,11 @@ struct PingStatistics {
     min_response_time: Duration,
     max_response_time: Duration,
     avg_response_time: Duration,
+    latency: LatencySummary,
+    /// Responses received, by status code
+    status_codes: BTreeMap<u16, u64>,
+    /// Failed requests by error kind
+    errors: BTreeMap<ErrorKind, u64>,
     total_time: Duration,
 }
 
@@ -115,9 +164,12 @@ struct HttpPinger {
     client: Client,
     url: String,
     args: Args,
+    body: Option<RequestBody>,
     stats: Arc<PingStatistics>,
+    latency: LatencyHistogram,
     running: Arc<AtomicBool>,
     sequence: Arc<AtomicU64>,
+    graph: Option<LatencyGraph>,
 }
 
 impl HttpPinger {
@@ -144,9 +196,25 @@ impl HttpPinger {
             .timeout(Duration::from_secs_f64(args.timeout))
             .build()?;
 
+        let body = if let Some(data) = &args.data {
+            Some(RequestBody::raw(data.as_str()))
+        } else if let Some(path) = &args.data_file {
+            let data = std::fs::read(path).map_err(|e| format!("Failed to read --data-file '{}': {}", path, e))?;
+            Some(RequestBody::raw(data))
+        } else if let Some(json) = &args.json_body {
+            let value: serde_json::Value = serde_json::from_str(json).map_err(|e| format!("Invalid --json-body: {}", e))?;
+            Some(RequestBody::json(&value))
+        } else {
+            None
+        };
+
+        // Without a terminal to redraw, fall back to printing each request
+        let graph = if args.graph { LatencyGraph::new() } else { None };
+
         Ok(Self {
             client,
             url: args.url.clone().unwrap_or_default(),
+            body,
             args,
             stats: Arc::new(PingStatistics {
                 total_requests: 0,
@@ -156,29 +224,39 @@ impl HttpPinger {
                 min_response_time: Duration::from_secs(u64::MAX),
                 max_response_time: Duration::from_secs(0),
                 avg_response_time: Duration::from_secs(0),
+                latency: LatencySummary::default(),
+                status_codes: BTreeMap::new(),
+                errors: BTreeMap::new(),
                 total_time: Duration::from_secs(0),
             }),
+            latency: LatencyHistogram::new(),
             running: Arc::new(AtomicBool::new(true)),
             sequence: Arc::new(AtomicU64::new(0)),
+            graph,
         })
     }
 
     async fn ping_once(&self) -> PingResult {
         let seq = self.sequence.fetch_add(1, Ordering::SeqCst) + 1;
-        let start = Instant::now();
-
-        let method = match self.args.method.to_uppercase().as_str() {
-            "GET" => Method::GET,
-            "POST" => Method::POST,
-            "PUT" => Method::PUT,
-            "DELETE" => Method::DELETE,
-            "HEAD" => Method::HEAD,
-            "OPTIONS" => Method::OPTIONS,
-            "PATCH" => Method::PATCH,
-            _ => Method::GET,
-        };
+        // The probe runs alongside the request and only adds connection phases;
+        // the request alone decides the result, within the client's timeout.
+        // The phases are only shown with -v, so only then is it worth the extra
+        // connection.
+        if !self.args.verbose {
+            return self.send_request(seq).await;
+        }
+        let timeout = Duration::from_secs_f64(self.args.timeout);
+        let (probe, mut result) = tokio::join!(ConnectionProbe::run(&self.url, timeout), self.send_request(seq));
+        result.timings.dns_time = probe.timings.dns_time;
+        result.timings.connect_time = probe.timings.connect_time;
+        result.timings.tls_time = probe.timings.tls_time;
+        result
+    }
 
-        let mut request_builder = self.client.request(method, &self.url);
+    async fn send_request(&self, seq: u64) -> PingResult {
+        let mut timings = PhaseTimings::default();
+        let start = Instant::now();
+        let mut request_builder = self.client.request(self.args.method.clone(), &self.url);
 
         // Use custom User-Agent if provided, otherwise use random one
         let user_agent = self.args.user_agent.as_deref().unwrap_or_else(|| Self::get_random_user_agent());
@@ -190,20 +268,48 @@ impl HttpPinger {
             }
         }
 
+        if let Some(body) = &self.body {
+            let has_content_type = self.args.headers.iter().any(|header| {
+                header.split_once(':').is_some_and(|(key, _)| key.trim().eq_ignore_ascii_case("Content-Type"))
+            });
+            if !has_content_type {
+                request_builder = request_builder.header("Content-Type", body.content_type);
+            }
+            request_builder = request_builder.body(body.data.clone());
+        }
+
         match request_builder.send().await {
             Ok(response) => {
                 let response_time = start.elapsed();
+                timings.headers_time = Some(response_time);
                 let status_code = response.status();
-                let success = status_code.is_success();
+
+                let transfer_start = Instant::now();
+                let body = response.bytes().await;
+                timings.transfer_time = Some(transfer_start.elapsed());
+
+                let (error_kind, error) = if !status_code.is_success() {
+                    (Some(ErrorKind::HttpStatus), Some(format!("Unexpected status code {}", status_code.as_u16())))
+                } else if let Err(e) = body {
+                    let kind = match ErrorKind::from_reqwest(&e) {
+                        ErrorKind::Other => ErrorKind::BodyReadError,
+                        kind => kind,
+                    };
+                    (Some(kind), Some(format!("Failed to read response body: {}", e)))
+                } else {
+                    (None, None)
+                };
 
                 PingResult {
                     sequence: seq,
                     url: self.url.clone(),
                     status_code: Some(status_code.as_u16()),
                     response_time,
-                    success,
-                    error: None,
+                    success: error_kind.is_none(),
+                    error,
+                    error_kind,
                     timestamp: chrono::Utc::now(),
+                    timings,
                 }
             }
             Err(err) => {
@@ -215,7 +321,9 @@ impl HttpPinger {
                     response_time,
                     success: false,
                     error: Some(err.to_string()),
+                    error_kind: Some(ErrorKind::from_reqwest(&err)),
                     timestamp: chrono::Utc::now(),
+                    timings,
                 }
             }
         }
@@ -230,6 +338,12 @@ impl HttpPinger {
         } else {
             stats.failed_requests += 1;
         }
+        if let Some(code) = result.status_code {
+            *stats.status_codes.entry(code).or_insert(0) += 1;
+        }
+        if let Some(kind) = result.error_kind {
+            *stats.errors.entry(kind).or_insert(0) += 1;
+        }
 
         stats.success_rate = (stats.successful_requests as f64 / stats.total_requests as f64) * 100.0;
 
@@ -240,8 +354,9 @@ impl HttpPinger {
             stats.max_response_time = result.response_time;
         }
 
-        let total_time_ms = (stats.avg_response_time.as_millis() as u64 * (stats.total_requests - 1)) + result.response_time.as_millis() as u64;
-        stats.avg_response_time = Duration::from_millis(total_time_ms / stats.total_requests);
+        self.latency.record(result.response_time);
+        stats.avg_response_time = self.latency.mean();
+        stats.latency = self.latency.summary();
     }
 
     fn format_response_time(&self, duration: Duration) -> String {
@@ -257,7 +372,7 @@ impl HttpPinger {
         }
     }
 
-    fn format_status_code(&self, status_code: Option<u16>) -> String {
+    fn format_status_code(&self, status_code: Option<u16>, error_kind: Option<ErrorKind>) -> String {
         match status_code {
             Some(code) => {
                 if self.args.no_color {
@@ -272,10 +387,11 @@ impl HttpPinger {
                 }
             }
             None => {
+                let label = output::failure_label(error_kind);
                 if self.args.no_color {
-                    "TIMEOUT/ERROR".to_string()
+                    label.to_string()
                 } else {
-                    "TIMEOUT/ERROR".red().to_string()
+                    label.red().to_string()
                 }
             }
         }
@@ -291,12 +407,14 @@ impl HttpPinger {
             return;
         }
 
-        let status_str = self.format_status_code(result.status_code);
+        let status_str = self.format_status_code(result.status_code, result.error_kind);
         let time_str = self.format_response_time(result.response_time);
         let success_indicator = if result.success {
             if self.args.no_color { "✓".to_string() } else { "✓".green().to_string() }
+        } else if self.args.no_color {
+            "✗".to_string()
         } else {
-            if self.args.no_color { "✗".to_string() } else { "✗".red().to_string() }
+            "✗".red().to_string()
         };
 
         if self.args.quiet {
@@ -310,8 +428,9 @@ impl HttpPinger {
                      time_str);
 
             if self.args.verbose {
-                if let Some(error) = &result.error {
-                    println!("  Error: {}", error);
+                println!("  Timing: {}", result.timings.summary());
+                if let (Some(error), Some(kind)) = (&result.error, result.error_kind) {
+                    println!("  Error ({}): {}", kind, error);
                 }
             }
         }
@@ -331,10 +450,23 @@ impl HttpPinger {
                  100.0 - self.stats.success_rate);
 
         if self.stats.successful_requests > 0 {
-            println!("round-trip min/avg/max = {}/{}/{} ms",
-                     self.stats.min_response_time.as_millis(),
-                     self.stats.avg_response_time.as_millis(),
-                     self.stats.max_response_time.as_millis());
+            let ms = |d: Duration| d.as_secs_f64() * 1000.0;
+            let latency = &self.stats.latency;
+            println!("round-trip min/avg/max/mdev = {:.3}/{:.3}/{:.3}/{:.3} ms",
+                     ms(self.stats.min_response_time),
+                     ms(self.stats.avg_response_time),
+                     ms(self.stats.max_response_time),
+                     ms(latency.stddev));
+            println!("percentiles p50/p90/p95/p99/p99.9 = {:.3}/{:.3}/{:.3}/{:.3}/{:.3} ms",
+                     ms(latency.p50),
+                     ms(latency.p90),
+                     ms(latency.p95),
+                     ms(latency.p99),
+                     ms(latency.p99_9));
+        }
+
+        for line in output::breakdown_lines(self.stats.total_requests, &self.stats.status_codes, &self.stats.errors) {
+            println!("{}", line);
         }
     }
 
@@ -357,7 +489,10 @@ impl HttpPinger {
 
             let result = self.ping_once().await;
             self.update_stats(&result);
-            self.print_result(&result);
+            match &mut self.graph {
+                Some(graph) => graph.update(&result, &self.stats)?,
+                None => self.print_result(&result),
+            }
 
             count += 1;
 
@@ -383,9 +518,9 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     }
 
     match args.command {
-        Some(Commands::Monitor { config }) => {
-            let config = Config::from_file(&config)?;
-            let monitor = Monitor::new(config)?;
+        Some(Commands::Monitor { config: path, watch, tui }) => {
+            let config = Config::from_file(&path)?;
+            let monitor = Monitor::new(config)?.with_reload(&path, watch).with_tui(tui);
             monitor.run().await?;
         }
         Some(Commands::Init { output }) => {
@@ -395,6 +530,11 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             println!("✅ Example configuration written to: {}", output);
             println!("📝 Edit the file and run: httpping monitor -c {}", output);
         }
+        Some(Commands::Validate { files }) => {
+            if !validate::run(&files) {
+                std::process::exit(1);
+            }
+        }
         Some(Commands::Ping { url, count, interval, timeout }) => {
             // Convert to legacy args format
             let legacy_args = Args {
@@ -403,14 +543,18 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 count,
                 interval,
                 timeout,
-                method: "GET".to_string(),
+                method: Method::GET,
                 headers: vec![],
+                data: None,
+                data_file: None,
+                json_body: None,
                 user_agent: None,
                 quiet: false,
                 verbose: false,
                 stats_only: false,
                 no_color: args.no_color,
                 json: args.json,
+                graph: args.graph,
             };
             
             let mut pinger = HttpPinger::new(legacy_args)?;
@@ -0,0 +1,51 @@
+use crate::monitor::TargetHealth;
+use crate::output;
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Body, Method, Request, Response, Server, StatusCode};
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::future::Future;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+
+/// Binds `addr` and returns a server future exposing `/metrics` for the
+/// shared target health map. Binding happens eagerly so a bad address is
+/// reported before monitoring starts.
+pub fn serve(
+    addr: SocketAddr,
+    targets: Arc<Mutex<HashMap<String, TargetHealth>>>,
+) -> Result<impl Future<Output = Result<(), hyper::Error>>, hyper::Error> {
+    let make_service = make_service_fn(move |_| {
+        let targets = Arc::clone(&targets);
+        async move {
+            Ok::<_, Infallible>(service_fn(move |request| {
+                let targets = Arc::clone(&targets);
+                async move { Ok::<_, Infallible>(handle(request, &targets)) }
+            }))
+        }
+    });
+
+    Ok(Server::try_bind(&addr)?.serve(make_service))
+}
+
+fn handle(
+    request: Request<Body>,
+    targets: &Arc<Mutex<HashMap<String, TargetHealth>>>,
+) -> Response<Body> {
+    match (request.method(), request.uri().path()) {
+        (&Method::GET, "/metrics") => {
+            let body = {
+                let targets_lock = targets.lock().unwrap();
+                output::prometheus_exposition(targets_lock.values())
+            };
+            Response::builder()
+                .header("Content-Type", "text/plain; version=0.0.4")
+                .body(Body::from(body))
+                .unwrap()
+        }
+        _ => Response::builder()
+            .status(StatusCode::NOT_FOUND)
+            .body(Body::from("Not Found\n"))
+            .unwrap(),
+    }
+}
@@ -1,14 +1,25 @@
-use crate::config::{Alert, AlertTrigger, Config, Target};
+use crate::assertions;
+use crate::config::{Alert, AlertTrigger, Assertion, Config, HealthThresholds, OutputFormat, Settings, Target};
+use crate::errors::ErrorKind;
+use crate::latency::{LatencyHistogram, LatencySummary};
+use crate::logfile::CheckLog;
+use crate::metrics;
+use crate::output;
+use crate::status;
+use crate::timing::{ConnectionProbe, PhaseTimings};
+use crate::tls::CertInfo;
+use crate::tui::{self, EventLevel, EventLog};
 use chrono::{DateTime, Utc};
 use colored::*;
-use reqwest::{Client, Method};
+use reqwest::Client;
 use serde::{Deserialize, Serialize};
-use std::collections::{HashMap, VecDeque};
-use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
-use std::time::{Duration, Instant};
+use std::time::{Duration, Instant, SystemTime};
+use tokio::task::JoinHandle;
 use tokio::time::sleep;
-use url::Url;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct HealthCheck {
@@ -18,9 +29,14 @@ pub struct HealthCheck {
     pub status_code: Option<u16>,
     pub response_time: Duration,
     pub error: Option<String>,
+    /// Class of the failure, if the check failed
+    #[serde(default)]
+    pub error_kind: Option<ErrorKind>,
     pub cert_expires_days: Option<u32>,
-    pub dns_time: Option<Duration>,
-    pub connect_time: Option<Duration>,
+    pub cert_issuer: Option<String>,
+    pub cert_subject: Option<String>,
+    #[serde(flatten)]
+    pub timings: PhaseTimings,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -29,18 +45,71 @@ pub struct TargetHealth {
     pub url: String,
     pub current_status: HealthStatus,
     pub consecutive_failures: u32,
+    /// Lifetime totals since the monitor started
     pub total_checks: u64,
     pub successful_checks: u64,
+    /// Responses received, by status code
+    pub status_codes: BTreeMap<u16, u64>,
+    /// Failed checks by error kind
+    pub error_counts: BTreeMap<ErrorKind, u64>,
+    pub lifetime_uptime_percentage: f64,
+    pub lifetime_avg_response_time: Duration,
+    /// Uptime and average over the last `health_check_window_minutes`
+    pub window_checks: u64,
     pub uptime_percentage: f64,
     pub avg_response_time: Duration,
     pub min_response_time: Duration,
     pub max_response_time: Duration,
+    pub latency: LatencySummary,
+    #[serde(skip)]
+    latency_histogram: LatencyHistogram,
     pub last_check: Option<DateTime<Utc>>,
     pub health_score: f64,
     pub recent_checks: VecDeque<HealthCheck>,
+    pub response_time_histogram: ResponseTimeHistogram,
+    #[serde(skip)]
+    window: chrono::Duration,
+    #[serde(skip)]
+    window_samples: VecDeque<WindowSample>,
+    #[serde(skip)]
+    thresholds: HealthThresholds,
+    #[serde(skip)]
+    max_consecutive_failures: u32,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone)]
+struct WindowSample {
+    timestamp: DateTime<Utc>,
+    success: bool,
+    response_time: Duration,
+}
+
+/// Upper bounds, in seconds, of the response time histogram buckets. These
+/// match the Prometheus client library defaults.
+pub const RESPONSE_TIME_BUCKETS: [f64; 11] = [0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0];
+
+/// Lifetime response time distribution, kept for the Prometheus exposition.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ResponseTimeHistogram {
+    /// Observations per bucket (not cumulative); anything above the last
+    /// bound is only reflected in `count`.
+    pub buckets: [u64; RESPONSE_TIME_BUCKETS.len()],
+    pub sum_seconds: f64,
+    pub count: u64,
+}
+
+impl ResponseTimeHistogram {
+    fn observe(&mut self, response_time: Duration) {
+        let seconds = response_time.as_secs_f64();
+        if let Some(i) = RESPONSE_TIME_BUCKETS.iter().position(|bound| seconds <= *bound) {
+            self.buckets[i] += 1;
+        }
+        self.sum_seconds += seconds;
+        self.count += 1;
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
 #[serde(rename_all = "snake_case")]
 pub enum HealthStatus {
     Healthy,
@@ -50,36 +119,106 @@ pub enum HealthStatus {
 }
 
 pub struct Monitor {
-    config: Config,
+    /// The config currently applied. Tasks read alerts and settings from here
+    /// on every check, so a reload reaches them without a restart.
+    config: Arc<Mutex<Config>>,
+    client: Client,
+    targets: Arc<Mutex<HashMap<String, TargetHealth>>>,
+    running: Arc<AtomicBool>,
+    incidents: Arc<Mutex<HashMap<String, Incident>>>,
+    /// When each `alert:target` last sent a firing notification. Kept after
+    /// incidents close so a flapping target still respects the cooldown.
+    last_notified: Arc<Mutex<HashMap<String, DateTime<Utc>>>>,
+    check_log: Option<Arc<Mutex<CheckLog>>>,
+    config_path: Option<PathBuf>,
+    watch: bool,
+    /// Set when the dashboard is shown; checks and notices go here instead of stdout
+    events: Option<Arc<Mutex<EventLog>>>,
+}
+
+/// Shared state handed to each target's check loop.
+#[derive(Clone)]
+struct TaskContext {
     client: Client,
     targets: Arc<Mutex<HashMap<String, TargetHealth>>>,
     running: Arc<AtomicBool>,
-    alert_cooldowns: Arc<Mutex<HashMap<String, DateTime<Utc>>>>,
+    config: Arc<Mutex<Config>>,
+    incidents: Arc<Mutex<HashMap<String, Incident>>>,
+    last_notified: Arc<Mutex<HashMap<String, DateTime<Utc>>>>,
+    check_log: Option<Arc<Mutex<CheckLog>>>,
+    events: Option<Arc<Mutex<EventLog>>>,
+}
+
+/// A running per-target check loop and the definition it was started with.
+struct TargetTask {
+    target: Target,
+    handle: JoinHandle<()>,
+}
+
+/// An alert that is currently firing for a target, keyed by `alert:target`.
+/// The entry is removed (and a resolved notification sent) once the alert's
+/// trigger conditions clear.
+#[derive(Debug, Clone, Serialize)]
+pub struct Incident {
+    pub alert: String,
+    pub target: String,
+    pub started_at: DateTime<Utc>,
+    /// `None` while the incident opened within the cooldown of an earlier
+    /// notification and hasn't notified yet; it then resolves silently too.
+    pub last_notified: Option<DateTime<Utc>>,
 }
 
 impl Monitor {
     pub fn new(config: Config) -> Result<Self, Box<dyn std::error::Error>> {
-        let client = Client::builder()
-            .timeout(Duration::from_secs_f64(config.settings.default_timeout))
-            .build()?;
+        // Timeouts are set per request from each target's timeout_seconds
+        let client = Client::builder().build()?;
 
         let mut targets = HashMap::new();
         for target in &config.targets {
             targets.insert(
                 target.name.clone(),
-                TargetHealth::new(target.clone()),
+                TargetHealth::new(target.clone(), &config.settings),
             );
         }
 
+        let check_log = match &config.settings.log_file {
+            Some(path) => {
+                let settings = &config.settings;
+                let log = CheckLog::open(path, settings.output_format.clone(), settings.log_rotation.clone())
+                    .map_err(|e| format!("Failed to open log file '{}': {}", path, e))?;
+                Some(Arc::new(Mutex::new(log)))
+            }
+            None => None,
+        };
+
         Ok(Self {
-            config,
+            config: Arc::new(Mutex::new(config)),
             client,
             targets: Arc::new(Mutex::new(targets)),
             running: Arc::new(AtomicBool::new(true)),
-            alert_cooldowns: Arc::new(Mutex::new(HashMap::new())),
+            incidents: Arc::new(Mutex::new(HashMap::new())),
+            last_notified: Arc::new(Mutex::new(HashMap::new())),
+            check_log,
+            config_path: None,
+            watch: false,
+            events: None,
         })
     }
 
+    /// Reloads the config from `path` on SIGHUP and, with `watch`, whenever
+    /// the file's modification time changes.
+    pub fn with_reload(mut self, path: &str, watch: bool) -> Self {
+        self.config_path = Some(PathBuf::from(path));
+        self.watch = watch;
+        self
+    }
+
+    /// Shows the full-screen dashboard instead of printing each check.
+    pub fn with_tui(mut self, enabled: bool) -> Self {
+        self.events = enabled.then(|| Arc::new(Mutex::new(EventLog::default())));
+        self
+    }
+
     pub async fn run(&self) -> Result<(), Box<dyn std::error::Error>> {
         let running = Arc::clone(&self.running);
         
@@ -87,71 +226,292 @@ impl Monitor {
             running.store(false, Ordering::SeqCst);
         })?;
 
-        println!("🚀 Starting HTTP monitor for {} targets...", self.config.targets.len());
-        
-        let mut handles = Vec::new();
-        
-        for target in &self.config.targets {
-            let target_clone = target.clone();
-            let client = self.client.clone();
+        let config = self.config.lock().unwrap().clone();
+        let output_format = config.settings.output_format.clone();
+        self.notice(&format!("🚀 Starting HTTP monitor for {} targets...", config.targets.len()));
+
+        if let Some(listen) = &config.settings.metrics_listen {
+            let addr: std::net::SocketAddr = listen
+                .parse()
+                .map_err(|e| format!("Invalid metrics_listen address '{}': {}", listen, e))?;
+            let server = metrics::serve(addr, Arc::clone(&self.targets))?;
+            tokio::spawn(server);
+            self.notice(&format!("📈 Serving Prometheus metrics on http://{}/metrics", addr));
+        }
+
+        if let Some(listen) = &config.settings.status_listen {
+            let addr: std::net::SocketAddr = listen
+                .parse()
+                .map_err(|e| format!("Invalid status_listen address '{}': {}", listen, e))?;
+            let server = status::serve(addr, Arc::clone(&self.targets), Arc::clone(&self.incidents))?;
+            tokio::spawn(server);
+            self.notice(&format!("🌐 Serving status page on http://{}/", addr));
+        }
+
+        if let (OutputFormat::Csv, None) = (&output_format, &self.events) {
+            println!("{}", output::CSV_HEADER);
+        }
+
+        let mut tasks = HashMap::new();
+        for target in &config.targets {
+            tasks.insert(target.name.clone(), self.spawn_target(target.clone()));
+        }
+
+        if let Some(events) = &self.events {
             let targets = Arc::clone(&self.targets);
+            let events = Arc::clone(events);
             let running = Arc::clone(&self.running);
-            let config = self.config.clone();
-            let alert_cooldowns = Arc::clone(&self.alert_cooldowns);
+            let dashboard = tokio::task::spawn_blocking(move || tui::run(targets, events, running));
 
-            let handle = tokio::spawn(async move {
-                Self::monitor_target(target_clone, client, targets, running, config, alert_cooldowns).await;
-            });
-            
-            handles.push(handle);
+            let reloads = self.wait_for_reloads(&mut tasks).await;
+            // Don't wait out in-flight checks once the dashboard is gone
+            for (_, task) in tasks {
+                task.handle.abort();
+            }
+            dashboard.await??;
+            reloads?;
+
+            self.print_final_summary();
+            return Ok(());
         }
 
         // Status reporting task
         let targets_for_status = Arc::clone(&self.targets);
         let running_for_status = Arc::clone(&self.running);
+        let status_format = output_format.clone();
         let status_handle = tokio::spawn(async move {
             while running_for_status.load(Ordering::SeqCst) {
                 sleep(Duration::from_secs(30)).await;
-                Self::print_status_summary(&targets_for_status);
+                Self::print_status_summary(&targets_for_status, &status_format);
             }
         });
 
-        handles.push(status_handle);
+        self.wait_for_reloads(&mut tasks).await?;
 
         // Wait for all tasks
-        for handle in handles {
-            let _ = handle.await;
+        for (_, task) in tasks {
+            let _ = task.handle.await;
         }
+        let _ = status_handle.await;
 
         self.print_final_summary();
         Ok(())
     }
 
-    async fn monitor_target(
-        target: Target,
-        client: Client,
-        targets: Arc<Mutex<HashMap<String, TargetHealth>>>,
-        running: Arc<AtomicBool>,
-        config: Config,
-        alert_cooldowns: Arc<Mutex<HashMap<String, DateTime<Utc>>>>,
-    ) {
-        while running.load(Ordering::SeqCst) {
+    fn spawn_target(&self, target: Target) -> TargetTask {
+        let context = TaskContext {
+            client: self.client.clone(),
+            targets: Arc::clone(&self.targets),
+            running: Arc::clone(&self.running),
+            config: Arc::clone(&self.config),
+            incidents: Arc::clone(&self.incidents),
+            last_notified: Arc::clone(&self.last_notified),
+            check_log: self.check_log.clone(),
+            events: self.events.clone(),
+        };
+
+        let handle = tokio::spawn(Self::monitor_target(target.clone(), context));
+        TargetTask { target, handle }
+    }
+
+    /// Runs until the monitor is stopped, reloading the config on SIGHUP or
+    /// (when watching) on file changes.
+    async fn wait_for_reloads(&self, tasks: &mut HashMap<String, TargetTask>) -> Result<(), Box<dyn std::error::Error>> {
+        #[cfg(unix)]
+        let mut hangup = tokio::signal::unix::signal(tokio::signal::unix::SignalKind::hangup())?;
+        let mut last_modified = self.config_path.as_deref().and_then(modified_time);
+        // Settings as last read from the file, which can differ from the
+        // applied ones for settings that need a restart
+        let mut loaded = self.config.lock().unwrap().settings.clone();
+
+        while self.running.load(Ordering::SeqCst) {
+            let mut reload = false;
+
+            #[cfg(unix)]
+            tokio::select! {
+                _ = hangup.recv() => reload = true,
+                _ = sleep(Duration::from_secs(1)) => {}
+            }
+            #[cfg(not(unix))]
+            sleep(Duration::from_secs(1)).await;
+
+            if self.watch {
+                let modified = self.config_path.as_deref().and_then(modified_time);
+                if modified != last_modified {
+                    last_modified = modified;
+                    reload = true;
+                }
+            }
+
+            if reload {
+                self.reload(tasks, &mut loaded).await;
+            }
+        }
+        Ok(())
+    }
+
+    /// Re-reads the config file and applies it. Unchanged targets keep running
+    /// with their stats, removed ones are stopped, and new or modified ones are
+    /// (re)started with fresh stats. An invalid file leaves everything as is.
+    ///
+    /// Settings that need a restart keep their running values; `loaded` holds
+    /// the ones last read from the file, so a change is only reported once.
+    /// Incidents of removed or changed targets and alerts are closed with a
+    /// resolved notification.
+    async fn reload(&self, tasks: &mut HashMap<String, TargetTask>, loaded: &mut Settings) {
+        let Some(path) = &self.config_path else {
+            self.notice("⚠️  Reload requested, but the monitor was not started from a config file");
+            return;
+        };
+        let path = path.display().to_string();
+        let mut new_config = match Config::from_file(&path) {
+            Ok(new_config) => new_config,
+            Err(e) => {
+                self.notice(&format!("⚠️  Reload failed, keeping the current config: {}", e));
+                return;
+            }
+        };
+
+        let (old, new) = (&*loaded, &new_config.settings);
+        let restart_only = [
+            ("output_format", old.output_format != new.output_format),
+            ("log_file", old.log_file != new.log_file),
+            ("log_rotation", old.log_rotation != new.log_rotation),
+            ("metrics_listen", old.metrics_listen != new.metrics_listen),
+            ("status_listen", old.status_listen != new.status_listen),
+        ];
+        for (setting, _) in restart_only.iter().filter(|(_, changed)| *changed) {
+            self.notice(&format!("⚠️  settings.{} changed; restart the monitor to apply it", setting));
+        }
+        *loaded = new_config.settings.clone();
+
+        let old_config = self.config.lock().unwrap().clone();
+        let (running, new) = (&old_config.settings, &mut new_config.settings);
+        new.output_format = running.output_format.clone();
+        new.log_file = running.log_file.clone();
+        new.log_rotation = running.log_rotation.clone();
+        new.metrics_listen = running.metrics_listen.clone();
+        new.status_listen = running.status_listen.clone();
+
+        let mut stopped = Vec::new();
+        let mut stopped_targets = HashMap::new();
+        tasks.retain(|name, task| {
+            let unchanged = new_config.targets.iter().any(|t| t.name == *name && *t == task.target);
+            if !unchanged {
+                task.handle.abort();
+                stopped.push(name.clone());
+                stopped_targets.insert(name.clone(), task.target.clone());
+            }
+            unchanged
+        });
+        let kept = tasks.len();
+
+        *self.config.lock().unwrap() = new_config.clone();
+        {
+            let mut targets = self.targets.lock().unwrap();
+            for name in &stopped {
+                targets.remove(name);
+            }
+            for target in &new_config.targets {
+                match targets.get_mut(&target.name) {
+                    Some(health) => health.reconfigure(target, &new_config.settings),
+                    None => {
+                        targets.insert(target.name.clone(), TargetHealth::new(target.clone(), &new_config.settings));
+                    }
+                }
+            }
+        }
+        let mut closed = Vec::new();
+        self.incidents.lock().unwrap().retain(|_, incident| {
+            let keep =
+                !stopped.contains(&incident.target) && new_config.alerts.iter().any(|a| a.name == incident.alert);
+            if !keep && incident.last_notified.is_some() {
+                closed.push(incident.clone());
+            }
+            keep
+        });
+
+        let mut started = 0;
+        for target in &new_config.targets {
+            if !tasks.contains_key(&target.name) {
+                tasks.insert(target.name.clone(), self.spawn_target(target.clone()));
+                started += 1;
+            }
+        }
+
+        let removed = stopped.iter().filter(|name| !tasks.contains_key(*name)).count();
+        let changed = stopped.len() - removed;
+        self.notice(&format!(
+            "🔄 Reloaded {}: {} added, {} removed, {} changed, {} unchanged",
+            path,
+            started - changed,
+            removed,
+            changed,
+            kept
+        ));
+
+        let now = Utc::now();
+        for incident in closed {
+            let alert = old_config.alerts.iter().find(|a| a.name == incident.alert);
+            let target = stopped_targets
+                .get(&incident.target)
+                .or_else(|| old_config.targets.iter().find(|t| t.name == incident.target));
+            let (Some(alert), Some(target)) = (alert, target) else {
+                continue;
+            };
+            let duration = now.signed_duration_since(incident.started_at);
+            if let Some(events) = &self.events {
+                events.lock().unwrap().push(
+                    EventLevel::Alert,
+                    format!(
+                        "✅ Resolved: {} - {} after {} (removed or changed by reload)",
+                        alert.name,
+                        target.name,
+                        format_incident_duration(duration)
+                    ),
+                );
+            }
+            Self::send_closed_by_reload(alert, target, duration).await;
+        }
+    }
+
+    async fn monitor_target(target: Target, context: TaskContext) {
+        while context.running.load(Ordering::SeqCst) {
             let start = Instant::now();
-            let check = Self::perform_health_check(&target, &client).await;
+            let probe = Self::wants_probe(&target, &context.config.lock().unwrap());
+            let check = Self::perform_health_check(&target, &context.client, probe).await;
             
             // Update target health
             {
-                let mut targets_lock = targets.lock().unwrap();
+                let mut targets_lock = context.targets.lock().unwrap();
                 if let Some(health) = targets_lock.get_mut(&target.name) {
                     health.update_with_check(check.clone());
                 }
             }
 
+            let (alerts, settings) = {
+                let config = context.config.lock().unwrap();
+                (config.alerts.clone(), config.settings.clone())
+            };
+
             // Check for alerts
-            Self::check_alerts(&target, &check, &config.alerts, &alert_cooldowns).await;
+            Self::check_alerts(&target, &check, &alerts, &context).await;
 
             // Print result
-            Self::print_check_result(&target, &check, &config.settings);
+            match &context.events {
+                Some(events) => events.lock().unwrap().push_check(&check),
+                None => Self::print_check_result(&target, &check, &settings),
+            }
+
+            if let Some(log) = &context.check_log {
+                if let Err(e) = log.lock().unwrap().write(&check) {
+                    let message = format!("⚠️  Failed to write log file: {}", e);
+                    match &context.events {
+                        Some(events) => events.lock().unwrap().push(EventLevel::Failure, message),
+                        None => eprintln!("{}", message),
+                    }
+                }
+            }
 
             let elapsed = start.elapsed();
             let interval = Duration::from_secs_f64(target.interval_seconds);
@@ -161,21 +521,53 @@ impl Monitor {
         }
     }
 
-    async fn perform_health_check(target: &Target, client: &Client) -> HealthCheck {
-        let start = Instant::now();
-        
-        let method = match target.method.to_uppercase().as_str() {
-            "GET" => Method::GET,
-            "POST" => Method::POST,
-            "PUT" => Method::PUT,
-            "DELETE" => Method::DELETE,
-            "HEAD" => Method::HEAD,
-            "OPTIONS" => Method::OPTIONS,
-            "PATCH" => Method::PATCH,
-            _ => Method::GET,
+    /// Whether checks of `target` need the probe connection: its phase timings
+    /// are only shown in verbose output and its certificate only matters to
+    /// `cert_expiring_days` alerts, so other checks spare the target the
+    /// extra connection.
+    fn wants_probe(target: &Target, config: &Config) -> bool {
+        config.settings.verbose
+            || config.alerts.iter().filter(|alert| target.notifies(alert)).any(|alert| {
+                alert.trigger_on.iter().any(|trigger| matches!(trigger, AlertTrigger::CertExpiringDays(_)))
+            })
+    }
+
+    async fn perform_health_check(target: &Target, client: &Client, probe: bool) -> HealthCheck {
+        // Connection phases (and the certificate, for HTTPS) come from a probe
+        // connection made alongside the request. It only adds details: the
+        // request alone decides the result, within its own timeout.
+        let timeout = Duration::from_secs_f64(target.timeout_seconds);
+        let probe = async {
+            if probe {
+                ConnectionProbe::run(&target.url, timeout).await
+            } else {
+                ConnectionProbe::default()
+            }
         };
+        let (probe, mut check) = tokio::join!(probe, Self::send_request(target, client, timeout));
+
+        check.timings.dns_time = probe.timings.dns_time;
+        check.timings.connect_time = probe.timings.connect_time;
+        check.timings.tls_time = probe.timings.tls_time;
+        check.cert_expires_days = probe.cert.as_ref().map(CertInfo::expires_days);
+        check.cert_issuer = probe.cert.as_ref().map(|c| c.issuer.clone());
+        check.cert_subject = probe.cert.map(|c| c.subject);
+        check
+    }
+
+    async fn send_request(target: &Target, client: &Client, timeout: Duration) -> HealthCheck {
+        let mut timings = PhaseTimings::default();
+        let request_body = target.request_body();
 
-        let mut request_builder = client.request(method, &target.url);
+        // Errors end up on the status page and metrics, so they leave out the
+        // URL, which may carry interpolated secrets
+        let describe = |e: &reqwest::Error, kind: ErrorKind, what: &str| match kind {
+            ErrorKind::Timeout => format!("Timed out {} after {}s", what, target.timeout_seconds),
+            _ => e.to_string(),
+        };
+
+        let start = Instant::now();
+        let mut request_builder = client.request(target.method.clone(), &target.url).timeout(timeout);
 
         // Add headers
         for (key, value) in &target.headers {
@@ -187,9 +579,27 @@ impl Monitor {
             request_builder = request_builder.header("User-Agent", Self::get_random_user_agent());
         }
 
-        match request_builder.send().await {
+        let result = match request_body {
+            Ok(body) => {
+                if let Some(body) = body {
+                    if !target.headers.keys().any(|key| key.eq_ignore_ascii_case("Content-Type")) {
+                        request_builder = request_builder.header("Content-Type", body.content_type);
+                    }
+                    request_builder = request_builder.body(body.data);
+                }
+                request_builder.send().await.map_err(|e| {
+                    let e = e.without_url();
+                    let kind = ErrorKind::from_reqwest(&e);
+                    (kind, describe(&e, kind, "waiting for a response"))
+                })
+            }
+            Err(e) => Err((ErrorKind::Other, e)),
+        };
+        let response_time = start.elapsed();
+
+        match result {
             Ok(response) => {
-                let response_time = start.elapsed();
+                timings.headers_time = Some(response_time);
                 let status_code = response.status().as_u16();
                 
                 // Check if status code is expected
@@ -199,104 +609,147 @@ impl Monitor {
                     target.expected_status.contains(&status_code)
                 };
 
-                // Check content if specified
-                let mut content_ok = true;
-                let mut error = None;
-
-                if let Some(expected_content) = &target.expected_content {
-                    match response.text().await {
-                        Ok(body) => {
-                            content_ok = body.contains(expected_content);
-                            if !content_ok {
-                                error = Some(format!("Expected content '{}' not found in response", expected_content));
-                            }
-                        }
-                        Err(e) => {
-                            content_ok = false;
-                            error = Some(format!("Failed to read response body: {}", e));
+                // The first failure decides the error kind: status, then
+                // reading the body, then assertions
+                let mut failures = Vec::new();
+                let mut error_kind = None;
+                if !status_ok {
+                    failures.push(format!("Unexpected status code {}", status_code));
+                    error_kind = Some(ErrorKind::HttpStatus);
+                }
+
+                // Check headers and content if specified
+                let mut mismatches = assertions::check_headers(&target.assertions, response.headers());
+
+                let transfer_start = Instant::now();
+                let body = response.text().await;
+                timings.transfer_time = Some(transfer_start.elapsed());
+
+                match body {
+                    Ok(body) => {
+                        if let Some(expected_content) = &target.expected_content {
+                            mismatches.extend(assertions::check_body(&[Assertion::Contains(expected_content.clone())], &body));
                         }
+                        mismatches.extend(assertions::check_body(&target.assertions, &body));
+                    }
+                    Err(e) => {
+                        let e = e.without_url();
+                        let kind = match ErrorKind::from_reqwest(&e) {
+                            ErrorKind::Other => ErrorKind::BodyReadError,
+                            kind => kind,
+                        };
+                        failures.push(match kind {
+                            ErrorKind::Timeout => describe(&e, kind, "reading the response body"),
+                            _ => format!("Failed to read response body: {}", e),
+                        });
+                        error_kind = error_kind.or(Some(kind));
                     }
                 }
-
-                // Check certificate expiry for HTTPS
-                let cert_expires_days = if target.url.starts_with("https://") {
-                    Self::check_cert_expiry(&target.url).await
-                } else {
-                    None
-                };
+                if !mismatches.is_empty() {
+                    failures.extend(mismatches);
+                    error_kind = error_kind.or(Some(ErrorKind::ContentMismatch));
+                }
 
                 HealthCheck {
                     target: target.name.clone(),
                     timestamp: Utc::now(),
-                    success: status_ok && content_ok,
+                    success: error_kind.is_none(),
                     status_code: Some(status_code),
                     response_time,
-                    error,
-                    cert_expires_days,
-                    dns_time: None, // TODO: Implement DNS timing
-                    connect_time: None, // TODO: Implement connection timing
+                    error: if failures.is_empty() { None } else { Some(failures.join("; ")) },
+                    error_kind,
+                    cert_expires_days: None,
+                    cert_issuer: None,
+                    cert_subject: None,
+                    timings,
                 }
             }
-            Err(err) => HealthCheck {
+            Err((kind, err)) => HealthCheck {
                 target: target.name.clone(),
                 timestamp: Utc::now(),
                 success: false,
                 status_code: None,
-                response_time: start.elapsed(),
-                error: Some(err.to_string()),
+                response_time,
+                error: Some(err),
+                error_kind: Some(kind),
                 cert_expires_days: None,
-                dns_time: None,
-                connect_time: None,
+                cert_issuer: None,
+                cert_subject: None,
+                timings,
             },
         }
     }
 
-    async fn check_cert_expiry(url: &str) -> Option<u32> {
-        // Simple certificate expiry check - in a real implementation you'd use rustls/webpki
-        // For now, we'll skip this complex implementation
-        None
-    }
-
     async fn check_alerts(
         target: &Target,
         check: &HealthCheck,
         alerts: &[Alert],
-        alert_cooldowns: &Arc<Mutex<HashMap<String, DateTime<Utc>>>>,
+        context: &TaskContext,
     ) {
-        for alert in alerts {
-            let should_alert = Self::should_trigger_alert(alert, target, check);
-            
-            if should_alert {
-                let now = Utc::now();
-                let cooldown_key = format!("{}:{}", alert.name, target.name);
-                
-                let should_send = {
-                    let mut cooldowns = alert_cooldowns.lock().unwrap();
-                    if let Some(last_sent) = cooldowns.get(&cooldown_key) {
-                        let cooldown_duration = chrono::Duration::minutes(alert.cooldown_minutes as i64);
-                        now.signed_duration_since(*last_sent) > cooldown_duration
-                    } else {
-                        true
-                    }
+        for alert in alerts.iter().filter(|alert| target.notifies(alert)) {
+            let should_alert = {
+                let targets_lock = context.targets.lock().unwrap();
+                targets_lock
+                    .get(&target.name)
+                    .is_some_and(|health| Self::should_trigger_alert(alert, health, check))
+            };
+
+            let now = Utc::now();
+            let key = format!("{}:{}", alert.name, target.name);
+
+            let notification = {
+                let mut incidents = context.incidents.lock().unwrap();
+                let mut last_notified = context.last_notified.lock().unwrap();
+                let mut incident = incidents.remove(&key);
+                let mut notified = last_notified.get(&key).copied();
+                let cooldown = chrono::Duration::minutes(alert.cooldown_minutes as i64);
+
+                let notification =
+                    next_notification(alert, target, &mut incident, &mut notified, should_alert, now, cooldown);
+                if let Some(incident) = incident {
+                    incidents.insert(key.clone(), incident);
+                }
+                if let Some(notified) = notified {
+                    last_notified.insert(key, notified);
+                }
+                notification
+            };
+
+            if let (Some(events), Some(notification)) = (&context.events, &notification) {
+                let message = match notification {
+                    AlertNotification::Firing => format!("🚨 Alert: {} - {}", alert.name, target.name),
+                    AlertNotification::Resolved(duration) => format!(
+                        "✅ Resolved: {} - {} after {}",
+                        alert.name,
+                        target.name,
+                        format_incident_duration(*duration)
+                    ),
                 };
+                events.lock().unwrap().push(EventLevel::Alert, message);
+            }
 
-                if should_send {
-                    Self::send_alert(alert, target, check).await;
-                    let mut cooldowns = alert_cooldowns.lock().unwrap();
-                    cooldowns.insert(cooldown_key, now);
+            match notification {
+                Some(AlertNotification::Firing) => Self::send_alert(alert, target, check).await,
+                Some(AlertNotification::Resolved(duration)) => {
+                    Self::send_resolved(alert, target, check, duration).await
                 }
+                None => {}
             }
         }
     }
 
-    fn should_trigger_alert(alert: &Alert, target: &Target, check: &HealthCheck) -> bool {
-        // This is simplified - in reality you'd track state over time
+    fn should_trigger_alert(alert: &Alert, health: &TargetHealth, check: &HealthCheck) -> bool {
         for trigger in &alert.trigger_on {
             match trigger {
-                AlertTrigger::ResponseTimeMs(threshold) => {
-                    if check.response_time.as_millis() as u64 > *threshold {
-                        return true;
-                    }
+                AlertTrigger::ConsecutiveFailures(threshold)
+                    if health.consecutive_failures >= *threshold =>
+                {
+                    return true;
+                }
+                AlertTrigger::ResponseTimeMs(threshold)
+                    if check.response_time.as_millis() as u64 > *threshold =>
+                {
+                    return true;
                 }
                 AlertTrigger::CertExpiringDays(days) => {
                     if let Some(cert_days) = check.cert_expires_days {
@@ -305,7 +758,12 @@ impl Monitor {
                         }
                     }
                 }
-                _ => {} // ConsecutiveFailures and HealthScoreBelow need more state tracking
+                AlertTrigger::HealthScoreBelow(threshold)
+                    if health.total_checks > 0 && health.health_score * 100.0 < *threshold =>
+                {
+                    return true;
+                }
+                _ => {}
             }
         }
         false
@@ -321,19 +779,91 @@ impl Monitor {
                     {"title": "URL", "value": target.url, "short": true},
                     {"title": "Status", "value": check.status_code.map_or("Error".to_string(), |c| c.to_string()), "short": true},
                     {"title": "Response Time", "value": format!("{}ms", check.response_time.as_millis()), "short": true},
+                    {"title": "Certificate Expires", "value": check.cert_expires_days.map_or("N/A".to_string(), |d| format!("{} days", d)), "short": true},
                     {"title": "Error", "value": check.error.as_deref().unwrap_or("N/A"), "short": false}
                 ]
             }]
         });
 
+        Self::post_webhook(&alert.webhook_url, &payload).await;
+    }
+
+    async fn send_resolved(alert: &Alert, target: &Target, check: &HealthCheck, duration: chrono::Duration) {
+        let payload = serde_json::json!({
+            "text": format!("✅ Resolved: {} - {}", alert.name, target.name),
+            "attachments": [{
+                "color": "good",
+                "fields": [
+                    {"title": "Target", "value": target.name, "short": true},
+                    {"title": "URL", "value": target.url, "short": true},
+                    {"title": "Status", "value": check.status_code.map_or("Error".to_string(), |c| c.to_string()), "short": true},
+                    {"title": "Response Time", "value": format!("{}ms", check.response_time.as_millis()), "short": true},
+                    {"title": "Incident Duration", "value": format_incident_duration(duration), "short": false}
+                ]
+            }]
+        });
+
+        Self::post_webhook(&alert.webhook_url, &payload).await;
+    }
+
+    /// Resolved notification for an incident that a reload closed because its
+    /// target or alert was removed or changed, not because the target recovered.
+    async fn send_closed_by_reload(alert: &Alert, target: &Target, duration: chrono::Duration) {
+        let payload = serde_json::json!({
+            "text": format!("✅ Resolved: {} - {}", alert.name, target.name),
+            "attachments": [{
+                "color": "good",
+                "fields": [
+                    {"title": "Target", "value": target.name, "short": true},
+                    {"title": "URL", "value": target.url, "short": true},
+                    {"title": "Reason", "value": "Target or alert removed or changed by a config reload", "short": false},
+                    {"title": "Incident Duration", "value": format_incident_duration(duration), "short": false}
+                ]
+            }]
+        });
+
+        Self::post_webhook(&alert.webhook_url, &payload).await;
+    }
+
+    async fn post_webhook(webhook_url: &str, payload: &serde_json::Value) {
         let client = Client::new();
-        let _ = client.post(&alert.webhook_url)
-            .json(&payload)
+        let _ = client.post(webhook_url)
+            .json(payload)
             .send()
             .await;
     }
 
-    fn print_check_result(target: &Target, check: &HealthCheck, settings: &crate::config::Settings) {
+    /// Shows a notice in the dashboard's event log, or prints it.
+    fn notice(&self, message: &str) {
+        match &self.events {
+            Some(events) => events.lock().unwrap().push(EventLevel::Info, message),
+            None => Self::print_notice(&self.config.lock().unwrap().settings.output_format, message),
+        }
+    }
+
+    /// Human-oriented messages go to stderr when stdout carries machine-readable output.
+    fn print_notice(format: &OutputFormat, message: &str) {
+        match format {
+            OutputFormat::Pretty => println!("{}", message),
+            _ => eprintln!("{}", message),
+        }
+    }
+
+    fn print_check_result(target: &Target, check: &HealthCheck, settings: &Settings) {
+        match settings.output_format {
+            OutputFormat::Pretty => {}
+            OutputFormat::Json => {
+                println!("{}", output::check_to_json(check));
+                return;
+            }
+            OutputFormat::Csv => {
+                println!("{}", output::check_to_csv(check));
+                return;
+            }
+            // Per-check lines have no exposition form; targets are reported in the summary
+            OutputFormat::Prometheus => return,
+        }
+
         if !settings.enable_colors {
             colored::control::set_override(false);
         }
@@ -345,7 +875,7 @@ impl Monitor {
         };
 
         let status_code_str = check.status_code
-            .map_or("ERROR".red().to_string(), |code| {
+            .map_or(output::failure_label(check.error_kind).red().to_string(), |code| {
                 match code {
                     200..=299 => code.to_string().green().to_string(),
                     300..=399 => code.to_string().yellow().to_string(),
@@ -369,16 +899,30 @@ impl Monitor {
                  status_code_str,
                  time_str);
 
+        if settings.verbose {
+            println!("    Timing: {}", check.timings.summary());
+        }
+
         if let Some(error) = &check.error {
             println!("    Error: {}", error.red());
         }
     }
 
-    fn print_status_summary(targets: &Arc<Mutex<HashMap<String, TargetHealth>>>) {
+    fn print_status_summary(targets: &Arc<Mutex<HashMap<String, TargetHealth>>>, format: &OutputFormat) {
         let targets_lock = targets.lock().unwrap();
+        match format {
+            OutputFormat::Pretty => {}
+            OutputFormat::Prometheus => {
+                print!("{}", output::prometheus_exposition(targets_lock.values()));
+                return;
+            }
+            // Json and Csv stream one record per check; a table would break the stream
+            OutputFormat::Json | OutputFormat::Csv => return,
+        }
+
         println!("\n📊 Status Summary:");
-        println!("{:<20} {:<10} {:<10} {:<15} {:<10}", "Target", "Status", "Uptime", "Avg Response", "Health");
-        println!("{}", "─".repeat(75));
+        println!("{:<20} {:<10} {:<10} {:<15} {:<10} {:<10} {:<10}", "Target", "Status", "Uptime", "Avg Response", "p95", "p99", "Health");
+        println!("{}", "─".repeat(97));
         
         for health in targets_lock.values() {
             let status = match health.current_status {
@@ -388,19 +932,58 @@ impl Monitor {
                 HealthStatus::Unknown => "Unknown".white(),
             };
 
-            println!("{:<20} {:<10} {:<10.1}% {:<15}ms {:<10.1}",
+            println!("{:<20} {:<10} {:<10.1}% {:<15}ms {:<8}ms {:<8}ms {:<10.1}",
                      health.name,
                      status,
                      health.uptime_percentage,
                      health.avg_response_time.as_millis(),
+                     health.latency.p95.as_millis(),
+                     health.latency.p99.as_millis(),
                      health.health_score * 100.0);
         }
         println!();
     }
 
     fn print_final_summary(&self) {
-        println!("\n🏁 Final Summary:");
-        Self::print_status_summary(&self.targets);
+        let format = self.config.lock().unwrap().settings.output_format.clone();
+        if let OutputFormat::Pretty = &format {
+            println!("\n🏁 Final Summary:");
+        }
+        Self::print_status_summary(&self.targets, &format);
+
+        let targets = self.targets.lock().unwrap();
+        let mut healths: Vec<&TargetHealth> = targets.values().collect();
+        healths.sort_by(|a, b| a.name.cmp(&b.name));
+        match format {
+            OutputFormat::Pretty => {
+                println!("📋 Response Breakdown:");
+                for health in healths {
+                    println!("{}", health.name.bold());
+                    for line in output::breakdown_lines(health.total_checks, &health.status_codes, &health.error_counts) {
+                        println!("  {}", line);
+                    }
+                }
+                println!();
+            }
+            // A closing record after the per-check lines
+            OutputFormat::Json => {
+                let summary: Vec<serde_json::Value> = healths
+                    .iter()
+                    .map(|health| {
+                        serde_json::json!({
+                            "target": health.name,
+                            "url": health.url,
+                            "total_checks": health.total_checks,
+                            "successful_checks": health.successful_checks,
+                            "status_codes": health.status_codes,
+                            "errors": health.error_counts,
+                        })
+                    })
+                    .collect();
+                println!("{}", serde_json::json!({ "summary": summary }));
+            }
+            OutputFormat::Csv | OutputFormat::Prometheus => {}
+        }
     }
 
     fn get_random_user_agent() -> &'static str {
@@ -408,8 +991,76 @@ impl Monitor {
     }
 }
 
+#[derive(Debug, PartialEq)]
+enum AlertNotification {
+    Firing,
+    Resolved(chrono::Duration),
+}
+
+/// Advances one `alert:target` pair by a check: opens, keeps or closes its
+/// `incident` and returns the notification to send, if any. `last_notified`
+/// is when the pair last fired; it outlives incidents, so there is at most one
+/// firing notification per `cooldown` whether the incident is still open or a
+/// new one started since. An incident that opened within the cooldown stays
+/// silent until it runs out, and resolves silently if it never notified.
+fn next_notification(
+    alert: &Alert,
+    target: &Target,
+    incident: &mut Option<Incident>,
+    last_notified: &mut Option<DateTime<Utc>>,
+    should_alert: bool,
+    now: DateTime<Utc>,
+    cooldown: chrono::Duration,
+) -> Option<AlertNotification> {
+    let cooled_down = last_notified.is_none_or(|last| now.signed_duration_since(last) > cooldown);
+    let notified_at = cooled_down.then_some(now);
+
+    let notification = match (should_alert, incident.as_mut()) {
+        (true, None) => {
+            *incident = Some(Incident {
+                alert: alert.name.clone(),
+                target: target.name.clone(),
+                started_at: now,
+                last_notified: notified_at,
+            });
+            cooled_down.then_some(AlertNotification::Firing)
+        }
+        (true, Some(open)) if cooled_down => {
+            open.last_notified = notified_at;
+            Some(AlertNotification::Firing)
+        }
+        (false, Some(_)) => incident
+            .take()
+            .filter(|closed| closed.last_notified.is_some())
+            .map(|closed| AlertNotification::Resolved(now.signed_duration_since(closed.started_at))),
+        _ => None,
+    };
+    if let Some(AlertNotification::Firing) = notification {
+        *last_notified = Some(now);
+    }
+    notification
+}
+
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+pub fn format_incident_duration(duration: chrono::Duration) -> String {
+    let secs = duration.num_seconds().max(0);
+    let (hours, minutes, seconds) = (secs / 3600, (secs % 3600) / 60, secs % 60);
+    if hours > 0 {
+        format!("{}h {}m {}s", hours, minutes, seconds)
+    } else if minutes > 0 {
+        format!("{}m {}s", minutes, seconds)
+    } else {
+        format!("{}s", seconds)
+    }
+}
+
 impl TargetHealth {
-    fn new(target: Target) -> Self {
+    fn new(target: Target, settings: &Settings) -> Self {
+        let thresholds = settings.health_for(&target);
+        let max_consecutive_failures = settings.max_consecutive_failures_for(&target);
         Self {
             name: target.name,
             url: target.url,
@@ -417,16 +1068,35 @@ impl TargetHealth {
             consecutive_failures: 0,
             total_checks: 0,
             successful_checks: 0,
+            status_codes: BTreeMap::new(),
+            error_counts: BTreeMap::new(),
+            lifetime_uptime_percentage: 0.0,
+            lifetime_avg_response_time: Duration::from_millis(0),
+            window_checks: 0,
             uptime_percentage: 0.0,
             avg_response_time: Duration::from_millis(0),
             min_response_time: Duration::from_millis(u64::MAX),
             max_response_time: Duration::from_millis(0),
+            latency: LatencySummary::default(),
+            latency_histogram: LatencyHistogram::new(),
             last_check: None,
             health_score: 1.0,
             recent_checks: VecDeque::with_capacity(100),
+            response_time_histogram: ResponseTimeHistogram::default(),
+            window: chrono::Duration::minutes(settings.health_check_window_minutes as i64),
+            window_samples: VecDeque::new(),
+            thresholds,
+            max_consecutive_failures,
         }
     }
 
+    /// Applies reloaded settings while keeping the collected stats.
+    fn reconfigure(&mut self, target: &Target, settings: &Settings) {
+        self.thresholds = settings.health_for(target);
+        self.max_consecutive_failures = settings.max_consecutive_failures_for(target);
+        self.window = chrono::Duration::minutes(settings.health_check_window_minutes as i64);
+    }
+
     fn update_with_check(&mut self, check: HealthCheck) {
         self.total_checks += 1;
         self.last_check = Some(check.timestamp);
@@ -437,6 +1107,14 @@ impl TargetHealth {
         } else {
             self.consecutive_failures += 1;
         }
+        if let Some(code) = check.status_code {
+            *self.status_codes.entry(code).or_insert(0) += 1;
+        }
+        if let Some(kind) = check.error_kind {
+            *self.error_counts.entry(kind).or_insert(0) += 1;
+        }
+
+        self.response_time_histogram.observe(check.response_time);
 
         // Update response time stats
         if check.response_time < self.min_response_time {
@@ -446,23 +1124,40 @@ impl TargetHealth {
             self.max_response_time = check.response_time;
         }
 
-        // Calculate average response time
-        let total_time_ms = (self.avg_response_time.as_millis() as u64 * (self.total_checks - 1)) + check.response_time.as_millis() as u64;
-        self.avg_response_time = Duration::from_millis(total_time_ms / self.total_checks);
+        // Lifetime average response time and percentiles
+        self.latency_histogram.record(check.response_time);
+        self.lifetime_avg_response_time = self.latency_histogram.mean();
+        self.latency = self.latency_histogram.summary();
+        self.lifetime_uptime_percentage = (self.successful_checks as f64 / self.total_checks as f64) * 100.0;
+
+        // Uptime and average response time over the sliding window
+        self.window_samples.push_back(WindowSample {
+            timestamp: check.timestamp,
+            success: check.success,
+            response_time: check.response_time,
+        });
+        let cutoff = check.timestamp - self.window;
+        while self.window_samples.front().is_some_and(|sample| sample.timestamp < cutoff) {
+            self.window_samples.pop_front();
+        }
 
-        // Update uptime percentage
-        self.uptime_percentage = (self.successful_checks as f64 / self.total_checks as f64) * 100.0;
+        let window_successes = self.window_samples.iter().filter(|sample| sample.success).count();
+        let window_time: Duration = self.window_samples.iter().map(|sample| sample.response_time).sum();
+        self.window_checks = self.window_samples.len() as u64;
+        self.uptime_percentage = (window_successes as f64 / self.window_checks as f64) * 100.0;
+        self.avg_response_time = window_time / self.window_samples.len() as u32;
 
         // Update current status
+        let thresholds = &self.thresholds;
         self.current_status = if self.consecutive_failures == 0 {
-            if self.uptime_percentage >= 99.0 {
+            if self.uptime_percentage >= thresholds.healthy_uptime {
                 HealthStatus::Healthy
-            } else if self.uptime_percentage >= 95.0 {
+            } else if self.uptime_percentage >= thresholds.degraded_uptime {
                 HealthStatus::Degraded
             } else {
                 HealthStatus::Unhealthy
             }
-        } else if self.consecutive_failures >= 3 {
+        } else if self.consecutive_failures >= self.max_consecutive_failures {
             HealthStatus::Unhealthy
         } else {
             HealthStatus::Degraded
@@ -470,17 +1165,9 @@ impl TargetHealth {
 
         // Calculate health score (0.0 to 1.0)
         let uptime_score = self.uptime_percentage / 100.0;
-        let response_time_score = if self.avg_response_time.as_millis() <= 500 {
-            1.0
-        } else if self.avg_response_time.as_millis() <= 2000 {
-            0.8
-        } else if self.avg_response_time.as_millis() <= 5000 {
-            0.5
-        } else {
-            0.2
-        };
-        
-        self.health_score = (uptime_score * 0.7) + (response_time_score * 0.3);
+        let response_time_score = thresholds.response_time_score(self.avg_response_time);
+
+        self.health_score = (uptime_score * thresholds.uptime_weight) + (response_time_score * thresholds.response_time_weight);
 
         // Store recent checks (keep last 100)
         self.recent_checks.push_back(check);
@@ -488,4 +1175,152 @@ impl TargetHealth {
             self.recent_checks.pop_front();
         }
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn minutes(n: i64) -> chrono::Duration {
+        chrono::Duration::minutes(n)
+    }
+
+    /// Feeds `should_alert` for each check, one minute apart, with a 30 minute
+    /// cooldown, returning the notification sent at each step.
+    fn run(
+        checks: &[bool],
+        incident: &mut Option<Incident>,
+        last_notified: &mut Option<DateTime<Utc>>,
+    ) -> Vec<Option<AlertNotification>> {
+        let config = Config::example();
+        let (alert, target) = (&config.alerts[0], &config.targets[0]);
+        let start = Utc::now();
+        checks
+            .iter()
+            .enumerate()
+            .map(|(i, should_alert)| {
+                let now = start + minutes(i as i64);
+                next_notification(alert, target, incident, last_notified, *should_alert, now, minutes(30))
+            })
+            .collect()
+    }
+
+    fn check_at(timestamp: DateTime<Utc>, success: bool, response_ms: u64) -> HealthCheck {
+        HealthCheck {
+            target: "Production API".to_string(),
+            timestamp,
+            success,
+            status_code: Some(if success { 200 } else { 503 }),
+            response_time: Duration::from_millis(response_ms),
+            error: None,
+            error_kind: (!success).then_some(ErrorKind::HttpStatus),
+            cert_expires_days: None,
+            cert_issuer: None,
+            cert_subject: None,
+            timings: PhaseTimings::default(),
+        }
+    }
+
+    #[test]
+    fn uptime_and_average_come_from_the_window() {
+        let config = Config::example();
+        assert_eq!(config.settings.health_check_window_minutes, 60);
+        let mut health = TargetHealth::new(config.targets[0].clone(), &config.settings);
+
+        let now = Utc::now();
+        health.update_with_check(check_at(now - minutes(180), false, 900));
+        health.update_with_check(check_at(now - minutes(120), false, 900));
+        // Exactly at the window's edge, so it stays in
+        health.update_with_check(check_at(now - minutes(60), true, 300));
+        health.update_with_check(check_at(now, true, 100));
+
+        assert_eq!(health.window_checks, 2);
+        assert_eq!(health.uptime_percentage, 100.0);
+        assert_eq!(health.avg_response_time, Duration::from_millis(200));
+        assert_eq!(health.current_status, HealthStatus::Healthy);
+
+        assert_eq!(health.total_checks, 4);
+        assert_eq!(health.successful_checks, 2);
+        assert_eq!(health.lifetime_uptime_percentage, 50.0);
+        // The lifetime mean comes from the HDR histogram, so allow for its precision
+        let lifetime_ms = health.lifetime_avg_response_time.as_secs_f64() * 1000.0;
+        assert!((lifetime_ms - 550.0).abs() < 5.0, "lifetime average {}ms", lifetime_ms);
+        assert_eq!(health.max_response_time, Duration::from_millis(900));
+    }
+
+    #[test]
+    fn window_drops_checks_as_time_moves_on() {
+        let config = Config::example();
+        let mut health = TargetHealth::new(config.targets[0].clone(), &config.settings);
+
+        let start = Utc::now();
+        health.update_with_check(check_at(start, false, 100));
+        assert_eq!(health.uptime_percentage, 0.0);
+        health.update_with_check(check_at(start + minutes(30), true, 100));
+        assert_eq!(health.uptime_percentage, 50.0);
+        health.update_with_check(check_at(start + minutes(61), true, 100));
+        assert_eq!(health.window_checks, 2);
+        assert_eq!(health.uptime_percentage, 100.0);
+        assert_eq!(health.lifetime_uptime_percentage, 2.0 / 3.0 * 100.0);
+    }
+
+    #[test]
+    fn first_failure_fires_and_opens_an_incident() {
+        let (mut incident, mut last_notified) = (None, None);
+        assert_eq!(run(&[true], &mut incident, &mut last_notified), [Some(AlertNotification::Firing)]);
+        let incident = incident.expect("incident should be open");
+        assert_eq!(incident.alert, "Slack Alerts");
+        assert_eq!(incident.last_notified, last_notified);
+        assert!(last_notified.is_some());
+    }
+
+    #[test]
+    fn open_incident_is_quiet_during_the_cooldown() {
+        let (mut incident, mut last_notified) = (None, None);
+        let sent = run(&[true; 31], &mut incident, &mut last_notified);
+        assert_eq!(sent[0], Some(AlertNotification::Firing));
+        assert!(sent[1..].iter().all(Option::is_none));
+    }
+
+    #[test]
+    fn open_incident_notifies_again_after_the_cooldown() {
+        let (mut incident, mut last_notified) = (None, None);
+        let sent = run(&[true; 32], &mut incident, &mut last_notified);
+        assert_eq!(sent[31], Some(AlertNotification::Firing));
+        assert_eq!(incident.unwrap().last_notified, last_notified);
+    }
+
+    #[test]
+    fn notified_incident_resolves_with_its_duration() {
+        let (mut incident, mut last_notified) = (None, None);
+        let sent = run(&[true, true, true, false], &mut incident, &mut last_notified);
+        assert_eq!(sent[3], Some(AlertNotification::Resolved(minutes(3))));
+        assert!(incident.is_none());
+    }
+
+    #[test]
+    fn incident_within_the_cooldown_resolves_silently() {
+        let mut incident = None;
+        let mut last_notified = Some(Utc::now() - minutes(5));
+        let sent = run(&[true, true, false], &mut incident, &mut last_notified);
+        assert_eq!(sent, [None, None, None]);
+        assert!(incident.is_none());
+    }
+
+    #[test]
+    fn flapping_target_notifies_once_per_cooldown() {
+        let (mut incident, mut last_notified) = (None, None);
+        let checks: Vec<bool> = (0..60).map(|i| i % 2 == 0).collect();
+        let sent: Vec<AlertNotification> =
+            run(&checks, &mut incident, &mut last_notified).into_iter().flatten().collect();
+        // Fires at minutes 0 and 32, each resolved a minute later
+        assert_eq!(
+            sent,
+            [
+                AlertNotification::Firing,
+                AlertNotification::Resolved(minutes(1)),
+                AlertNotification::Firing,
+                AlertNotification::Resolved(minutes(1)),
+            ]
+        );
+    }
+}
@@ -0,0 +1,303 @@
+use crate::errors::ErrorKind;
+use crate::monitor::{HealthCheck, HealthStatus, TargetHealth, RESPONSE_TIME_BUCKETS};
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::time::Duration;
+
+pub const CSV_HEADER: &str =
+    "timestamp,target,success,status_code,response_time_ms,error,error_kind,\
+cert_expires_days,probe_dns_ms,probe_connect_ms,probe_tls_ms,headers_ms,transfer_ms";
+
+pub fn check_to_json(check: &HealthCheck) -> String {
+    serde_json::to_string(check).unwrap()
+}
+
+/// Uncolored single-line form of the pretty output, for log files.
+pub fn check_to_text(check: &HealthCheck) -> String {
+    let mut line = format!(
+        "[{}] {} {} | {} | {}ms",
+        check.timestamp.to_rfc3339(),
+        if check.success { "✓" } else { "✗" },
+        check.target,
+        check
+            .status_code
+            .map_or(failure_label(check.error_kind).to_string(), |c| c
+                .to_string()),
+        check.response_time.as_millis()
+    );
+    if let Some(error) = &check.error {
+        let _ = write!(line, " | Error: {}", error);
+    }
+    line
+}
+
+pub fn check_to_csv(check: &HealthCheck) -> String {
+    let fields = [
+        check.timestamp.to_rfc3339(),
+        csv_escape(&check.target),
+        check.success.to_string(),
+        check.status_code.map_or(String::new(), |c| c.to_string()),
+        millis(check.response_time),
+        check.error.as_deref().map_or(String::new(), csv_escape),
+        check.error_kind.map_or(String::new(), |k| k.to_string()),
+        check
+            .cert_expires_days
+            .map_or(String::new(), |d| d.to_string()),
+        check.timings.dns_time.map_or(String::new(), millis),
+        check.timings.connect_time.map_or(String::new(), millis),
+        check.timings.tls_time.map_or(String::new(), millis),
+        check.timings.headers_time.map_or(String::new(), millis),
+        check.timings.transfer_time.map_or(String::new(), millis),
+    ];
+    fields.join(",")
+}
+
+/// Table of responses by status code and failures by error kind, as
+/// indented lines with each count's share of `total`.
+pub fn breakdown_lines(
+    total: u64,
+    status_codes: &BTreeMap<u16, u64>,
+    errors: &BTreeMap<ErrorKind, u64>,
+) -> Vec<String> {
+    let share = |count: u64| count as f64 / total.max(1) as f64 * 100.0;
+    let mut lines = Vec::new();
+    if !status_codes.is_empty() {
+        lines.push("responses by status code:".to_string());
+        for (code, count) in status_codes {
+            lines.push(format!(
+                "  {:<20} {:>8}  ({:.1}%)",
+                code,
+                count,
+                share(*count)
+            ));
+        }
+    }
+    if !errors.is_empty() {
+        lines.push("failures by error kind:".to_string());
+        for (kind, count) in errors {
+            lines.push(format!(
+                "  {:<20} {:>8}  ({:.1}%)",
+                kind.as_str(),
+                count,
+                share(*count)
+            ));
+        }
+    }
+    lines
+}
+
+/// Shown in place of the status code for checks that got no response.
+pub fn failure_label(error_kind: Option<ErrorKind>) -> &'static str {
+    if error_kind == Some(ErrorKind::Timeout) {
+        "TIMEOUT"
+    } else {
+        "ERROR"
+    }
+}
+
+fn millis(duration: Duration) -> String {
+    format!("{:.3}", duration.as_secs_f64() * 1000.0)
+}
+
+fn csv_escape(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Renders target health in the Prometheus text exposition format.
+pub fn prometheus_exposition<'a>(healths: impl IntoIterator<Item = &'a TargetHealth>) -> String {
+    let mut healths: Vec<&TargetHealth> = healths.into_iter().collect();
+    healths.sort_by(|a, b| a.name.cmp(&b.name));
+
+    let mut out = String::new();
+    write_metric(
+        &mut out,
+        &healths,
+        "httpping_up",
+        "gauge",
+        "Whether the most recent check succeeded.",
+        |h| {
+            h.recent_checks
+                .back()
+                .map(|c| if c.success { 1.0 } else { 0.0 })
+        },
+    );
+    write_metric(
+        &mut out,
+        &healths,
+        "httpping_checks_total",
+        "counter",
+        "Total number of checks performed.",
+        |h| Some(h.total_checks as f64),
+    );
+    write_metric(
+        &mut out,
+        &healths,
+        "httpping_checks_successful_total",
+        "counter",
+        "Number of successful checks.",
+        |h| Some(h.successful_checks as f64),
+    );
+    write_metric(
+        &mut out,
+        &healths,
+        "httpping_uptime_percentage",
+        "gauge",
+        "Percentage of successful checks.",
+        |h| Some(h.uptime_percentage),
+    );
+    write_metric(
+        &mut out,
+        &healths,
+        "httpping_response_time_avg_seconds",
+        "gauge",
+        "Average response time.",
+        |h| Some(h.avg_response_time.as_secs_f64()),
+    );
+    write_metric(
+        &mut out,
+        &healths,
+        "httpping_health_score",
+        "gauge",
+        "Health score between 0 and 1.",
+        |h| Some(h.health_score),
+    );
+    write_metric(
+        &mut out,
+        &healths,
+        "httpping_consecutive_failures",
+        "gauge",
+        "Number of consecutive failed checks.",
+        |h| Some(h.consecutive_failures as f64),
+    );
+    write_metric(
+        &mut out,
+        &healths,
+        "httpping_healthy",
+        "gauge",
+        "Whether the target status is healthy.",
+        |h| {
+            Some(if matches!(h.current_status, HealthStatus::Healthy) {
+                1.0
+            } else {
+                0.0
+            })
+        },
+    );
+    write_metric(
+        &mut out,
+        &healths,
+        "httpping_cert_expires_days",
+        "gauge",
+        "Days until the TLS certificate expires.",
+        |h| {
+            h.recent_checks
+                .back()
+                .and_then(|c| c.cert_expires_days)
+                .map(f64::from)
+        },
+    );
+    write_error_counts(&mut out, &healths);
+    write_response_time_histogram(&mut out, &healths);
+
+    out
+}
+
+fn write_error_counts(out: &mut String, healths: &[&TargetHealth]) {
+    let name = "httpping_check_errors_total";
+    let _ = writeln!(
+        out,
+        "# HELP {} Number of failed checks by error kind.",
+        name
+    );
+    let _ = writeln!(out, "# TYPE {} counter", name);
+    for health in healths {
+        for (kind, count) in &health.error_counts {
+            let _ = writeln!(
+                out,
+                "{}{{{},kind=\"{}\"}} {}",
+                name,
+                labels(health),
+                kind,
+                count
+            );
+        }
+    }
+}
+
+fn write_response_time_histogram(out: &mut String, healths: &[&TargetHealth]) {
+    let name = "httpping_response_time_seconds";
+    let _ = writeln!(out, "# HELP {} Response time of checks.", name);
+    let _ = writeln!(out, "# TYPE {} histogram", name);
+    for health in healths {
+        let histogram = &health.response_time_histogram;
+        let labels = labels(health);
+        let mut cumulative = 0;
+        for (bound, count) in RESPONSE_TIME_BUCKETS.iter().zip(histogram.buckets) {
+            cumulative += count;
+            let _ = writeln!(
+                out,
+                "{}_bucket{{{},le=\"{}\"}} {}",
+                name, labels, bound, cumulative
+            );
+        }
+        let _ = writeln!(
+            out,
+            "{}_bucket{{{},le=\"+Inf\"}} {}",
+            name, labels, histogram.count
+        );
+        let _ = writeln!(out, "{}_sum{{{}}} {}", name, labels, histogram.sum_seconds);
+        let _ = writeln!(out, "{}_count{{{}}} {}", name, labels, histogram.count);
+    }
+}
+
+fn write_metric(
+    out: &mut String,
+    healths: &[&TargetHealth],
+    name: &str,
+    kind: &str,
+    help: &str,
+    value: impl Fn(&TargetHealth) -> Option<f64>,
+) {
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} {}", name, kind);
+    for health in healths {
+        if let Some(v) = value(health) {
+            let _ = writeln!(out, "{}{{{}}} {}", name, labels(health), v);
+        }
+    }
+}
+
+fn labels(health: &TargetHealth) -> String {
+    format!(
+        "target=\"{}\",url=\"{}\"",
+        label_escape(&health.name),
+        label_escape(&public_url(&health.url))
+    )
+}
+
+fn label_escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// `url` without credentials, query or fragment, for the unauthenticated
+/// metrics and status endpoints. Secrets interpolated into a target URL
+/// usually live in one of those parts.
+pub fn public_url(url: &str) -> String {
+    match url::Url::parse(url) {
+        Ok(mut url) => {
+            let _ = url.set_username("");
+            let _ = url.set_password(None);
+            url.set_query(None);
+            url.set_fragment(None);
+            url.to_string()
+        }
+        Err(_) => String::new(),
+    }
+}
@@ -0,0 +1,288 @@
+use crate::monitor::{format_incident_duration, HealthStatus, Incident, TargetHealth};
+use crate::output;
+use chrono::Utc;
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Body, Method, Request, Response, Server, StatusCode};
+use percent_encoding::{percent_decode_str, utf8_percent_encode, NON_ALPHANUMERIC};
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::fmt::Write;
+use std::future::Future;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+
+type Targets = Arc<Mutex<HashMap<String, TargetHealth>>>;
+type Incidents = Arc<Mutex<HashMap<String, Incident>>>;
+
+/// Binds `addr` and returns a server future with an HTML status page at `/`
+/// and a JSON API under `/api/targets`, both read from the shared target
+/// health map. Like the metrics server, binding happens eagerly.
+pub fn serve(
+    addr: SocketAddr,
+    targets: Targets,
+    incidents: Incidents,
+) -> Result<impl Future<Output = Result<(), hyper::Error>>, hyper::Error> {
+    let make_service = make_service_fn(move |_| {
+        let (targets, incidents) = (Arc::clone(&targets), Arc::clone(&incidents));
+        async move {
+            Ok::<_, Infallible>(service_fn(move |request| {
+                let (targets, incidents) = (Arc::clone(&targets), Arc::clone(&incidents));
+                async move { Ok::<_, Infallible>(handle(request, &targets, &incidents)) }
+            }))
+        }
+    });
+
+    Ok(Server::try_bind(&addr)?.serve(make_service))
+}
+
+fn handle(request: Request<Body>, targets: &Targets, incidents: &Incidents) -> Response<Body> {
+    if request.method() != Method::GET {
+        return json_error(StatusCode::METHOD_NOT_ALLOWED, "only GET is supported");
+    }
+
+    let path = request.uri().path().trim_end_matches('/');
+    match path {
+        "" | "/index.html" => {
+            let page = {
+                let targets = targets.lock().unwrap();
+                let incidents = incidents.lock().unwrap();
+                render_page(&sorted(&targets), &incidents)
+            };
+            Response::builder()
+                .header("Content-Type", "text/html; charset=utf-8")
+                .body(Body::from(page))
+                .unwrap()
+        }
+        "/api/targets" => {
+            let body = {
+                let targets = targets.lock().unwrap();
+                let incidents = incidents.lock().unwrap();
+                let summaries: Vec<serde_json::Value> = sorted(&targets)
+                    .into_iter()
+                    .map(|health| target_json(health, &incidents))
+                    .collect();
+                serde_json::Value::from(summaries)
+            };
+            json(StatusCode::OK, &body)
+        }
+        _ => match path
+            .strip_prefix("/api/targets/")
+            .and_then(|rest| rest.strip_suffix("/checks"))
+        {
+            Some(name) => {
+                let name = percent_decode_str(name).decode_utf8_lossy();
+                let targets = targets.lock().unwrap();
+                match targets.get(name.as_ref()) {
+                    Some(health) => json(StatusCode::OK, &serde_json::json!(health.recent_checks)),
+                    None => {
+                        json_error(StatusCode::NOT_FOUND, &format!("unknown target '{}'", name))
+                    }
+                }
+            }
+            None => json_error(StatusCode::NOT_FOUND, "not found"),
+        },
+    }
+}
+
+fn sorted(targets: &HashMap<String, TargetHealth>) -> Vec<&TargetHealth> {
+    let mut healths: Vec<&TargetHealth> = targets.values().collect();
+    healths.sort_by(|a, b| a.name.cmp(&b.name));
+    healths
+}
+
+/// A target's health without its recent checks, which have their own
+/// endpoint, plus the incidents currently open for it.
+fn target_json(health: &TargetHealth, incidents: &HashMap<String, Incident>) -> serde_json::Value {
+    let mut value = serde_json::json!(health);
+    if let Some(fields) = value.as_object_mut() {
+        fields.remove("recent_checks");
+        fields.insert(
+            "url".to_string(),
+            serde_json::json!(output::public_url(&health.url)),
+        );
+        let open: Vec<&Incident> = incidents
+            .values()
+            .filter(|i| i.target == health.name)
+            .collect();
+        fields.insert("incidents".to_string(), serde_json::json!(open));
+    }
+    value
+}
+
+fn json(status: StatusCode, body: &serde_json::Value) -> Response<Body> {
+    Response::builder()
+        .status(status)
+        .header("Content-Type", "application/json")
+        .body(Body::from(body.to_string()))
+        .unwrap()
+}
+
+fn json_error(status: StatusCode, message: &str) -> Response<Body> {
+    json(status, &serde_json::json!({ "error": message }))
+}
+
+fn render_page(targets: &[&TargetHealth], incidents: &HashMap<String, Incident>) -> String {
+    let down = targets
+        .iter()
+        .filter(|t| t.current_status == HealthStatus::Unhealthy)
+        .count();
+    let degraded = targets
+        .iter()
+        .filter(|t| t.current_status == HealthStatus::Degraded)
+        .count();
+    let (banner_class, banner) = match (down, degraded) {
+        (0, 0) => ("healthy", "All systems operational".to_string()),
+        (0, n) => (
+            "degraded",
+            format!("{} of {} services degraded", n, targets.len()),
+        ),
+        (n, _) => (
+            "unhealthy",
+            format!("{} of {} services down", n, targets.len()),
+        ),
+    };
+
+    let mut page = String::new();
+    let _ = write!(
+        page,
+        r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<meta name="viewport" content="width=device-width, initial-scale=1">
+<meta http-equiv="refresh" content="30">
+<title>Service Status</title>
+<style>{}</style>
+</head>
+<body>
+<main>
+<h1>Service Status</h1>
+<div class="banner {}">{}</div>
+"#,
+        STYLE,
+        banner_class,
+        escape(&banner)
+    );
+
+    let mut open: Vec<&Incident> = incidents.values().collect();
+    open.sort_by_key(|incident| incident.started_at);
+    if !open.is_empty() {
+        page.push_str("<h2>Active incidents</h2>\n<ul class=\"incidents\">\n");
+        for incident in open {
+            let _ = writeln!(
+                page,
+                "<li><strong>{}</strong>: {} <span class=\"muted\">since {} ({})</span></li>",
+                escape(&incident.target),
+                escape(&incident.alert),
+                incident.started_at.format("%Y-%m-%d %H:%M UTC"),
+                format_incident_duration(Utc::now().signed_duration_since(incident.started_at))
+            );
+        }
+        page.push_str("</ul>\n");
+    }
+
+    page.push_str("<h2>Services</h2>\n");
+    for health in targets {
+        let (class, label) = status_badge(health.current_status);
+        let _ = writeln!(
+            page,
+            r#"<section class="target">
+<div class="row"><strong>{}</strong><span class="badge {}">{}</span></div>
+<div class="bars">{}</div>
+<div class="row muted"><span>{:.2}% uptime</span><span>{}ms average</span><span><a href="/api/targets/{}/checks">recent checks</a></span></div>
+</section>"#,
+            escape(&health.name),
+            class,
+            label,
+            uptime_bars(health),
+            health.uptime_percentage,
+            health.avg_response_time.as_millis(),
+            utf8_percent_encode(&health.name, NON_ALPHANUMERIC)
+        );
+    }
+
+    let _ = write!(
+        page,
+        r#"<footer class="muted">Updated {} · refreshes every 30s · <a href="/api/targets">JSON</a></footer>
+</main>
+</body>
+</html>
+"#,
+        Utc::now().format("%Y-%m-%d %H:%M:%S UTC")
+    );
+    page
+}
+
+/// One bar per recent check, oldest first, with the details in its tooltip.
+fn uptime_bars(health: &TargetHealth) -> String {
+    if health.recent_checks.is_empty() {
+        return "<span class=\"muted\">No checks yet</span>".to_string();
+    }
+
+    let mut bars = String::new();
+    for check in &health.recent_checks {
+        let outcome = match (check.status_code, &check.error) {
+            (_, Some(error)) => error.clone(),
+            (Some(code), None) => code.to_string(),
+            (None, None) => "OK".to_string(),
+        };
+        let _ = write!(
+            bars,
+            r#"<span class="bar {}" title="{} · {}ms · {}"></span>"#,
+            if check.success { "up" } else { "down" },
+            check.timestamp.format("%Y-%m-%d %H:%M:%S UTC"),
+            check.response_time.as_millis(),
+            escape(&outcome)
+        );
+    }
+    bars
+}
+
+fn status_badge(status: HealthStatus) -> (&'static str, &'static str) {
+    match status {
+        HealthStatus::Healthy => ("healthy", "Operational"),
+        HealthStatus::Degraded => ("degraded", "Degraded"),
+        HealthStatus::Unhealthy => ("unhealthy", "Down"),
+        HealthStatus::Unknown => ("unknown", "Unknown"),
+    }
+}
+
+fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+const STYLE: &str = "
+body { margin: 0; background: #f6f7f9; color: #1f2328; font-family: -apple-system, 'Segoe UI', Helvetica, Arial, sans-serif; }
+main { max-width: 860px; margin: 0 auto; padding: 2rem 1rem; }
+h1 { font-size: 1.6rem; }
+h2 { font-size: 1.1rem; margin-top: 2rem; }
+a { color: inherit; }
+.muted { color: #6e7781; font-size: 0.9rem; }
+.banner { padding: 1rem 1.25rem; border-radius: 6px; color: #fff; font-weight: 600; }
+.banner.healthy { background: #2da44e; }
+.banner.degraded { background: #bf8700; }
+.banner.unhealthy { background: #cf222e; }
+.incidents { padding-left: 1.25rem; }
+.target { background: #fff; border: 1px solid #d0d7de; border-radius: 6px; padding: 1rem; margin-bottom: 0.75rem; }
+.row { display: flex; justify-content: space-between; gap: 1rem; }
+.badge { font-size: 0.85rem; font-weight: 600; }
+.badge.healthy { color: #2da44e; }
+.badge.degraded { color: #bf8700; }
+.badge.unhealthy { color: #cf222e; }
+.badge.unknown { color: #6e7781; }
+.bars { display: flex; gap: 2px; height: 32px; margin: 0.75rem 0 0.5rem; }
+.bar { flex: 1; border-radius: 2px; }
+.bar.up { background: #2da44e; }
+.bar.down { background: #cf222e; }
+";
@@ -0,0 +1,98 @@
+use crate::tls::{self, CertInfo};
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+use tokio::net::{lookup_host, TcpStream};
+use url::Url;
+
+/// Breakdown of where the time of a check went.
+///
+/// reqwest does not expose its connection phases, so DNS, connect and TLS are
+/// measured on a separate probe connection made alongside the request. They
+/// describe how long those phases take against the host, not the ones the
+/// request itself went through (which may reuse a pooled connection).
+/// `headers_time` runs from sending the request until the response headers
+/// arrive, so it includes whatever connection setup reqwest did itself.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PhaseTimings {
+    pub dns_time: Option<Duration>,
+    pub connect_time: Option<Duration>,
+    pub tls_time: Option<Duration>,
+    pub headers_time: Option<Duration>,
+    pub transfer_time: Option<Duration>,
+}
+
+impl PhaseTimings {
+    /// e.g. `probe dns=1.2ms connect=3.4ms tls=20.1ms | headers=45.0ms transfer=2.3ms`
+    pub fn summary(&self) -> String {
+        let probe = Self::join(&[
+            ("dns", self.dns_time),
+            ("connect", self.connect_time),
+            ("tls", self.tls_time),
+        ]);
+        let request = Self::join(&[
+            ("headers", self.headers_time),
+            ("transfer", self.transfer_time),
+        ]);
+
+        match (probe.is_empty(), request.is_empty()) {
+            (true, _) => request,
+            (false, true) => format!("probe {}", probe),
+            (false, false) => format!("probe {} | {}", probe, request),
+        }
+    }
+
+    fn join(phases: &[(&str, Option<Duration>)]) -> String {
+        phases
+            .iter()
+            .filter_map(|(name, time)| {
+                time.map(|t| format!("{}={:.1}ms", name, t.as_secs_f64() * 1000.0))
+            })
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct ConnectionProbe {
+    pub timings: PhaseTimings,
+    pub cert: Option<CertInfo>,
+}
+
+impl ConnectionProbe {
+    /// Resolves, connects and (for https) handshakes with the host in `url`,
+    /// recording each phase. Phases after a failure or the timeout are left as
+    /// `None`; failures are for the request itself to report.
+    pub async fn run(url: &str, timeout: Duration) -> Self {
+        let mut probe = Self::default();
+        let _ = tokio::time::timeout(timeout, probe.measure(url)).await;
+        probe
+    }
+
+    async fn measure(&mut self, url: &str) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let url = Url::parse(url)?;
+        let host = url.host_str().ok_or("URL has no host")?;
+        let port = url.port_or_known_default().ok_or("URL has no port")?;
+        // IPv6 literals come back bracketed from host_str()
+        let host = host.trim_start_matches('[').trim_end_matches(']');
+
+        let start = Instant::now();
+        let addr = lookup_host((host, port))
+            .await?
+            .next()
+            .ok_or("host resolved to no addresses")?;
+        self.timings.dns_time = Some(start.elapsed());
+
+        let start = Instant::now();
+        let stream = TcpStream::connect(addr).await?;
+        self.timings.connect_time = Some(start.elapsed());
+
+        if url.scheme() == "https" {
+            let start = Instant::now();
+            let cert = tls::handshake(stream, host).await?;
+            self.timings.tls_time = Some(start.elapsed());
+            self.cert = Some(cert);
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,163 @@
+use chrono::{DateTime, TimeZone, Utc};
+use rustls::client::{ServerCertVerified, ServerCertVerifier};
+use rustls::{Certificate, ClientConfig, ServerName};
+use std::sync::Arc;
+use std::time::SystemTime;
+use tokio::net::TcpStream;
+use tokio_rustls::TlsConnector;
+use x509_parser::prelude::*;
+
+/// Details of the leaf certificate presented by a TLS server.
+#[derive(Debug, Clone)]
+pub struct CertInfo {
+    pub subject: String,
+    pub issuer: String,
+    pub not_after: DateTime<Utc>,
+}
+
+impl CertInfo {
+    pub fn from_der(der: &[u8]) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        let (_, cert) = X509Certificate::from_der(der)?;
+        let not_after = Utc
+            .timestamp_opt(cert.validity().not_after.timestamp(), 0)
+            .single()
+            .ok_or("certificate notAfter is out of range")?;
+
+        Ok(Self {
+            subject: cert.subject().to_string(),
+            issuer: cert.issuer().to_string(),
+            not_after,
+        })
+    }
+
+    /// Whole days until the certificate expires, or 0 if it already has.
+    pub fn expires_days(&self) -> u32 {
+        let remaining = self.not_after.signed_duration_since(Utc::now()).num_days();
+        remaining.max(0) as u32
+    }
+}
+
+/// Accepts any certificate chain. We only read the certificate here; trust is
+/// enforced by the real request going through reqwest.
+struct AcceptAnyCert;
+
+impl ServerCertVerifier for AcceptAnyCert {
+    fn verify_server_cert(
+        &self,
+        _end_entity: &Certificate,
+        _intermediates: &[Certificate],
+        _server_name: &ServerName,
+        _scts: &mut dyn Iterator<Item = &[u8]>,
+        _ocsp_response: &[u8],
+        _now: SystemTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        Ok(ServerCertVerified::assertion())
+    }
+}
+
+fn inspect_config() -> Arc<ClientConfig> {
+    let config = ClientConfig::builder()
+        .with_safe_defaults()
+        .with_custom_certificate_verifier(Arc::new(AcceptAnyCert))
+        .with_no_client_auth();
+    Arc::new(config)
+}
+
+/// Completes a TLS handshake over `stream` and returns the leaf certificate
+/// the server presented.
+pub async fn handshake(
+    stream: TcpStream,
+    host: &str,
+) -> Result<CertInfo, Box<dyn std::error::Error + Send + Sync>> {
+    let server_name = ServerName::try_from(host)?;
+    let connector = TlsConnector::from(inspect_config());
+    let tls = connector.connect(server_name, stream).await?;
+    let (_, session) = tls.get_ref();
+    let leaf = session
+        .peer_certificates()
+        .and_then(|certs| certs.first())
+        .ok_or("server presented no certificate")?;
+    CertInfo::from_der(&leaf.0)
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::timing::ConnectionProbe;
+    use chrono::{Datelike, Duration as ChronoDuration};
+    use rcgen::{CertificateParams, DistinguishedName, DnType};
+    use rustls::{PrivateKey, ServerConfig};
+    use std::time::Duration;
+    use tokio::net::TcpListener;
+    use tokio_rustls::TlsAcceptor;
+
+    /// Serves a self-signed certificate for `localhost`, valid for `days`,
+    /// on an ephemeral port of 127.0.0.1.
+    pub(crate) async fn serve_cert(days: i64) -> u16 {
+        let expiry = Utc::now() + ChronoDuration::days(days);
+        let mut params = CertificateParams::new(vec!["localhost".to_string()]);
+        params.distinguished_name = DistinguishedName::new();
+        params
+            .distinguished_name
+            .push(DnType::CommonName, "httpping test");
+        params.not_after =
+            rcgen::date_time_ymd(expiry.year(), expiry.month() as u8, expiry.day() as u8);
+        let cert = rcgen::Certificate::from_params(params).unwrap();
+
+        let config = ServerConfig::builder()
+            .with_safe_defaults()
+            .with_no_client_auth()
+            .with_single_cert(
+                vec![Certificate(cert.serialize_der().unwrap())],
+                PrivateKey(cert.serialize_private_key_der()),
+            )
+            .unwrap();
+        let acceptor = TlsAcceptor::from(Arc::new(config));
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let acceptor = acceptor.clone();
+                tokio::spawn(async move {
+                    let _ = acceptor.accept(stream).await;
+                });
+            }
+        });
+        port
+    }
+
+    #[tokio::test]
+    async fn handshake_reads_leaf_certificate() {
+        let port = serve_cert(10).await;
+        let stream = TcpStream::connect(("127.0.0.1", port)).await.unwrap();
+        let cert = handshake(stream, "localhost").await.unwrap();
+
+        // notAfter is midnight, so part of today is already gone
+        assert!(
+            (9..=10).contains(&cert.expires_days()),
+            "expires_days = {}",
+            cert.expires_days()
+        );
+        assert_eq!(cert.subject, "CN=httpping test");
+        assert_eq!(cert.issuer, "CN=httpping test");
+    }
+
+    #[tokio::test]
+    async fn probe_reports_certificate_and_phases() {
+        let port = serve_cert(3).await;
+        let url = format!("https://127.0.0.1:{}/", port);
+        let probe = ConnectionProbe::run(&url, Duration::from_secs(5)).await;
+
+        assert!(probe.timings.dns_time.is_some());
+        assert!(probe.timings.connect_time.is_some());
+        assert!(probe.timings.tls_time.is_some());
+        let cert = probe.cert.expect("probe should read the certificate");
+        assert!(
+            (2..=3).contains(&cert.expires_days()),
+            "expires_days = {}",
+            cert.expires_days()
+        );
+        assert_eq!(cert.subject, "CN=httpping test");
+    }
+}
@@ -0,0 +1,524 @@
+use crate::errors::ErrorKind;
+use crate::latency::LatencySummary;
+use crate::monitor::{HealthCheck, HealthStatus, TargetHealth};
+use crate::output;
+use chrono::{DateTime, Local, Utc};
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{
+    Block, Borders, Cell, List, ListItem, Paragraph, Row, Sparkline, SparklineBar, Table,
+    TableState,
+};
+use ratatui::{DefaultTerminal, Frame};
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::io;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+const MAX_EVENTS: usize = 1000;
+const TREND_WIDTH: usize = 20;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EventLevel {
+    Info,
+    Success,
+    Failure,
+    Alert,
+}
+
+#[derive(Debug, Clone)]
+pub struct LogEntry {
+    pub timestamp: DateTime<Utc>,
+    pub level: EventLevel,
+    pub message: String,
+}
+
+/// Checks, alerts and notices shown in the dashboard's event log, in place
+/// of the lines the monitor would otherwise print.
+#[derive(Debug, Default)]
+pub struct EventLog {
+    entries: VecDeque<LogEntry>,
+}
+
+impl EventLog {
+    pub fn push(&mut self, level: EventLevel, message: impl Into<String>) {
+        if self.entries.len() >= MAX_EVENTS {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(LogEntry {
+            timestamp: Utc::now(),
+            level,
+            message: message.into(),
+        });
+    }
+
+    pub fn push_check(&mut self, check: &HealthCheck) {
+        let status = check.status_code.map_or(
+            output::failure_label(check.error_kind).to_string(),
+            |code| code.to_string(),
+        );
+        let mut message = format!(
+            "{} | {} | {}ms",
+            check.target,
+            status,
+            check.response_time.as_millis()
+        );
+        if let Some(error) = &check.error {
+            message.push_str(" | ");
+            message.push_str(error);
+        }
+        let level = if check.success {
+            EventLevel::Success
+        } else {
+            EventLevel::Failure
+        };
+        self.push(level, message);
+    }
+}
+
+/// What the dashboard shows of a target, copied out so the health map isn't
+/// locked while drawing.
+struct TargetView {
+    name: String,
+    url: String,
+    status: HealthStatus,
+    consecutive_failures: u32,
+    total_checks: u64,
+    successful_checks: u64,
+    uptime_percentage: f64,
+    lifetime_uptime_percentage: f64,
+    avg_response_time: Duration,
+    latency: LatencySummary,
+    health_score: f64,
+    /// Response time and success of each recent check, oldest first
+    recent: Vec<(Duration, bool)>,
+    last: Option<String>,
+    last_error: Option<String>,
+    cert_expires_days: Option<u32>,
+    status_codes: BTreeMap<u16, u64>,
+    error_counts: BTreeMap<ErrorKind, u64>,
+}
+
+impl From<&TargetHealth> for TargetView {
+    fn from(health: &TargetHealth) -> Self {
+        let last = health.recent_checks.back();
+        Self {
+            name: health.name.clone(),
+            url: health.url.clone(),
+            status: health.current_status,
+            consecutive_failures: health.consecutive_failures,
+            total_checks: health.total_checks,
+            successful_checks: health.successful_checks,
+            uptime_percentage: health.uptime_percentage,
+            lifetime_uptime_percentage: health.lifetime_uptime_percentage,
+            avg_response_time: health.avg_response_time,
+            latency: health.latency.clone(),
+            health_score: health.health_score,
+            recent: health
+                .recent_checks
+                .iter()
+                .map(|c| (c.response_time, c.success))
+                .collect(),
+            last: last.map(|check| {
+                check.status_code.map_or(
+                    output::failure_label(check.error_kind).to_string(),
+                    |code| code.to_string(),
+                )
+            }),
+            last_error: last.and_then(|check| check.error.clone()),
+            cert_expires_days: last.and_then(|check| check.cert_expires_days),
+            status_codes: health.status_codes.clone(),
+            error_counts: health.error_counts.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum SortBy {
+    Name,
+    Status,
+    Uptime,
+    ResponseTime,
+}
+
+impl SortBy {
+    fn next(self) -> Self {
+        match self {
+            SortBy::Name => SortBy::Status,
+            SortBy::Status => SortBy::Uptime,
+            SortBy::Uptime => SortBy::ResponseTime,
+            SortBy::ResponseTime => SortBy::Name,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            SortBy::Name => "name",
+            SortBy::Status => "status",
+            SortBy::Uptime => "uptime",
+            SortBy::ResponseTime => "response time",
+        }
+    }
+}
+
+/// Which statuses to show; `None` shows every target.
+fn next_filter(filter: Option<HealthStatus>) -> Option<HealthStatus> {
+    match filter {
+        None => Some(HealthStatus::Healthy),
+        Some(HealthStatus::Healthy) => Some(HealthStatus::Degraded),
+        Some(HealthStatus::Degraded) => Some(HealthStatus::Unhealthy),
+        Some(HealthStatus::Unhealthy) => Some(HealthStatus::Unknown),
+        Some(HealthStatus::Unknown) => None,
+    }
+}
+
+struct Dashboard {
+    targets: Arc<Mutex<HashMap<String, TargetHealth>>>,
+    events: Arc<Mutex<EventLog>>,
+    table: TableState,
+    sort: SortBy,
+    filter: Option<HealthStatus>,
+    show_detail: bool,
+    /// Entries scrolled back from the newest event
+    log_scroll: usize,
+}
+
+/// Runs the dashboard until the user quits or `running` is cleared, then
+/// restores the terminal. Quitting clears `running` to stop the monitor.
+pub fn run(
+    targets: Arc<Mutex<HashMap<String, TargetHealth>>>,
+    events: Arc<Mutex<EventLog>>,
+    running: Arc<AtomicBool>,
+) -> io::Result<()> {
+    let mut terminal = ratatui::try_init()?;
+    let mut dashboard = Dashboard {
+        targets,
+        events,
+        table: TableState::default().with_selected(0),
+        sort: SortBy::Name,
+        filter: None,
+        show_detail: false,
+        log_scroll: 0,
+    };
+    let result = dashboard.event_loop(&mut terminal, &running);
+    ratatui::restore();
+    running.store(false, Ordering::SeqCst);
+    result
+}
+
+impl Dashboard {
+    fn event_loop(
+        &mut self,
+        terminal: &mut DefaultTerminal,
+        running: &AtomicBool,
+    ) -> io::Result<()> {
+        while running.load(Ordering::SeqCst) {
+            terminal.draw(|frame| self.draw(frame))?;
+
+            if !event::poll(Duration::from_millis(250))? {
+                continue;
+            }
+            let Event::Key(key) = event::read()? else {
+                continue;
+            };
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+            match key.code {
+                KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+                KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                    return Ok(())
+                }
+                KeyCode::Down | KeyCode::Char('j') => self.table.select_next(),
+                KeyCode::Up | KeyCode::Char('k') => self.table.select_previous(),
+                KeyCode::Enter | KeyCode::Char('d') => self.show_detail = !self.show_detail,
+                KeyCode::Char('s') => self.sort = self.sort.next(),
+                KeyCode::Char('f') => {
+                    self.filter = next_filter(self.filter);
+                    self.table.select(Some(0));
+                }
+                KeyCode::PageUp => self.log_scroll += 5,
+                KeyCode::PageDown => self.log_scroll = self.log_scroll.saturating_sub(5),
+                KeyCode::End => self.log_scroll = 0,
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// The targets to list, filtered and sorted.
+    fn visible(&self) -> Vec<TargetView> {
+        let mut visible: Vec<TargetView> = self
+            .targets
+            .lock()
+            .unwrap()
+            .values()
+            .filter(|h| self.filter.is_none_or(|status| h.current_status == status))
+            .map(TargetView::from)
+            .collect();
+
+        match self.sort {
+            SortBy::Name => visible.sort_by(|a, b| a.name.cmp(&b.name)),
+            SortBy::Status => visible.sort_by_key(|h| (status_rank(&h.status), h.name.clone())),
+            SortBy::Uptime => {
+                visible.sort_by(|a, b| a.uptime_percentage.total_cmp(&b.uptime_percentage))
+            }
+            SortBy::ResponseTime => visible.sort_by_key(|h| Reverse(h.avg_response_time)),
+        }
+        visible
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let visible = self.visible();
+        if visible.is_empty() {
+            self.table.select(None);
+        } else if self.table.selected().is_none_or(|i| i >= visible.len()) {
+            self.table.select(Some(visible.len() - 1));
+        }
+
+        let [header, main, log] = Layout::vertical([
+            Constraint::Length(1),
+            Constraint::Min(8),
+            Constraint::Length(12),
+        ])
+        .areas(frame.area());
+
+        let filter = self.filter.as_ref().map_or("all", status_label);
+        let title = Line::from(vec![
+            Span::styled(" httpping monitor ", Style::new().add_modifier(Modifier::BOLD)),
+            Span::raw(format!(
+                "| {} targets | sort: {} | filter: {} | ↑↓ select  enter details  s sort  f filter  PgUp/PgDn log  q quit",
+                visible.len(),
+                self.sort.label(),
+                filter
+            )),
+        ]);
+        frame.render_widget(Paragraph::new(title), header);
+
+        let selected = self.table.selected().and_then(|i| visible.get(i));
+        match selected {
+            Some(health) if self.show_detail => {
+                let [table, detail] =
+                    Layout::horizontal([Constraint::Percentage(60), Constraint::Percentage(40)])
+                        .areas(main);
+                self.draw_table(frame, table, &visible);
+                draw_detail(frame, detail, health);
+            }
+            _ => self.draw_table(frame, main, &visible),
+        }
+
+        self.draw_log(frame, log);
+    }
+
+    fn draw_table(&mut self, frame: &mut Frame, area: Rect, visible: &[TargetView]) {
+        let header = Row::new([
+            "Target", "Status", "Uptime", "Avg", "p95", "Health", "Last", "Trend",
+        ])
+        .style(Style::new().add_modifier(Modifier::BOLD));
+        let rows = visible.iter().map(|health| {
+            Row::new(vec![
+                Cell::from(health.name.clone()),
+                Cell::from(status_label(&health.status)).style(status_style(&health.status)),
+                Cell::from(format!("{:.1}%", health.uptime_percentage)),
+                Cell::from(format!("{}ms", health.avg_response_time.as_millis())),
+                Cell::from(format!("{}ms", health.latency.p95.as_millis())),
+                Cell::from(format!("{:.1}", health.health_score * 100.0)),
+                Cell::from(health.last.clone().unwrap_or_else(|| "-".to_string())),
+                Cell::from(trend(&health.recent)),
+            ])
+        });
+        let widths = [
+            Constraint::Fill(2),
+            Constraint::Length(10),
+            Constraint::Length(8),
+            Constraint::Length(8),
+            Constraint::Length(8),
+            Constraint::Length(7),
+            Constraint::Length(8),
+            Constraint::Length(TREND_WIDTH as u16),
+        ];
+        let table = Table::new(rows, widths)
+            .header(header)
+            .block(Block::default().borders(Borders::ALL).title(" Targets "))
+            .row_highlight_style(Style::new().add_modifier(Modifier::REVERSED))
+            .highlight_symbol("▶ ");
+        frame.render_stateful_widget(table, area, &mut self.table);
+    }
+
+    fn draw_log(&mut self, frame: &mut Frame, area: Rect) {
+        let events = self.events.lock().unwrap();
+        let height = area.height.saturating_sub(2) as usize;
+        self.log_scroll = self
+            .log_scroll
+            .min(events.entries.len().saturating_sub(height));
+        let end = events.entries.len() - self.log_scroll;
+        let start = end.saturating_sub(height);
+
+        let items: Vec<ListItem> = events
+            .entries
+            .range(start..end)
+            .map(|entry| {
+                let (symbol, color) = match entry.level {
+                    EventLevel::Info => ("•", Color::Cyan),
+                    EventLevel::Success => ("✓", Color::Green),
+                    EventLevel::Failure => ("✗", Color::Red),
+                    EventLevel::Alert => ("!", Color::Magenta),
+                };
+                ListItem::new(Line::from(vec![
+                    Span::styled(
+                        format!(
+                            "[{}] ",
+                            entry.timestamp.with_timezone(&Local).format("%H:%M:%S")
+                        ),
+                        Style::new().fg(Color::DarkGray),
+                    ),
+                    Span::styled(format!("{} ", symbol), Style::new().fg(color)),
+                    Span::raw(entry.message.clone()),
+                ]))
+            })
+            .collect();
+
+        let title = if self.log_scroll > 0 {
+            format!(
+                " Events (scrolled back {}, End to follow) ",
+                self.log_scroll
+            )
+        } else {
+            " Events ".to_string()
+        };
+        frame.render_widget(
+            List::new(items).block(Block::default().borders(Borders::ALL).title(title)),
+            area,
+        );
+    }
+}
+
+fn draw_detail(frame: &mut Frame, area: Rect, health: &TargetView) {
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(format!(" {} ", health.name));
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let [info, chart] = Layout::vertical([Constraint::Min(6), Constraint::Length(5)]).areas(inner);
+
+    let ms = |d: Duration| d.as_millis();
+    let mut lines = vec![
+        Line::from(health.url.clone()),
+        Line::from(vec![
+            Span::raw("Status: "),
+            Span::styled(status_label(&health.status), status_style(&health.status)),
+            Span::raw(format!(
+                "  consecutive failures: {}",
+                health.consecutive_failures
+            )),
+        ]),
+        Line::from(format!(
+            "Checks: {} ({} ok)  uptime {:.1}% window / {:.1}% lifetime",
+            health.total_checks,
+            health.successful_checks,
+            health.uptime_percentage,
+            health.lifetime_uptime_percentage
+        )),
+        Line::from(format!(
+            "Latency: avg {}ms  p50 {}ms  p95 {}ms  p99 {}ms",
+            ms(health.avg_response_time),
+            ms(health.latency.p50),
+            ms(health.latency.p95),
+            ms(health.latency.p99)
+        )),
+    ];
+    if let Some(days) = health.cert_expires_days {
+        lines.push(Line::from(format!("Certificate expires in {} days", days)));
+    }
+    if let Some(error) = &health.last_error {
+        lines.push(Line::styled(
+            format!("Last error: {}", error),
+            Style::new().fg(Color::Red),
+        ));
+    }
+    for line in output::breakdown_lines(
+        health.total_checks,
+        &health.status_codes,
+        &health.error_counts,
+    ) {
+        lines.push(Line::from(line));
+    }
+    frame.render_widget(Paragraph::new(lines), info);
+
+    let shown = health.recent.len().saturating_sub(chart.width as usize);
+    let bars: Vec<SparklineBar> = health.recent[shown..]
+        .iter()
+        .map(|(response_time, success)| {
+            let bar = SparklineBar::from(response_time.as_millis() as u64);
+            if *success {
+                bar
+            } else {
+                bar.style(Some(Style::new().fg(Color::Red)))
+            }
+        })
+        .collect();
+    let sparkline = Sparkline::default()
+        .block(Block::default().title("Response time (recent checks)"))
+        .style(Style::new().fg(Color::Green))
+        .data(bars);
+    frame.render_widget(sparkline, chart);
+}
+
+/// Recent response times as block characters, with failures as `×`.
+fn trend(checks: &[(Duration, bool)]) -> String {
+    const BLOCKS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+    let recent = &checks[checks.len().saturating_sub(TREND_WIDTH)..];
+    let max = recent
+        .iter()
+        .map(|(time, _)| *time)
+        .max()
+        .unwrap_or_default()
+        .as_secs_f64();
+    recent
+        .iter()
+        .map(|(response_time, success)| {
+            if !success {
+                return '×';
+            }
+            let level = if max > 0.0 {
+                response_time.as_secs_f64() / max
+            } else {
+                0.0
+            };
+            BLOCKS[((level * 7.0).round() as usize).min(7)]
+        })
+        .collect()
+}
+
+fn status_rank(status: &HealthStatus) -> u8 {
+    match status {
+        HealthStatus::Unhealthy => 0,
+        HealthStatus::Degraded => 1,
+        HealthStatus::Unknown => 2,
+        HealthStatus::Healthy => 3,
+    }
+}
+
+fn status_label(status: &HealthStatus) -> &'static str {
+    match status {
+        HealthStatus::Healthy => "Healthy",
+        HealthStatus::Degraded => "Degraded",
+        HealthStatus::Unhealthy => "Unhealthy",
+        HealthStatus::Unknown => "Unknown",
+    }
+}
+
+fn status_style(status: &HealthStatus) -> Style {
+    let color = match status {
+        HealthStatus::Healthy => Color::Green,
+        HealthStatus::Degraded => Color::Yellow,
+        HealthStatus::Unhealthy => Color::Red,
+        HealthStatus::Unknown => Color::Gray,
+    };
+    Style::new().fg(color)
+}
@@ -0,0 +1,519 @@
+use crate::config::{Config, HealthThresholds, LoadError};
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use yaml_rust2::parser::{Event, MarkedEventReceiver, Parser};
+use yaml_rust2::scanner::Marker;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+/// A semantic problem in a config, located by its path within the document
+/// (e.g. `targets[1].url`).
+#[derive(Debug, Clone)]
+pub struct Problem {
+    pub severity: Severity,
+    pub path: String,
+    pub message: String,
+}
+
+impl Problem {
+    pub fn error(path: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            severity: Severity::Error,
+            path: path.into(),
+            message: message.into(),
+        }
+    }
+
+    fn warning(path: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            severity: Severity::Warning,
+            path: path.into(),
+            message: message.into(),
+        }
+    }
+
+    /// `file:line: path: message`, falling back to the nearest enclosing node
+    /// for fields that were left to their defaults.
+    pub fn display(&self, file: &str, lines: &LineIndex) -> String {
+        let location = match lines.line_for(&self.path) {
+            Some(line) => format!("{}:{}", file, line),
+            None => file.to_string(),
+        };
+        let severity = match self.severity {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        };
+        format!(
+            "{}: {}: {}: {}",
+            location, severity, self.path, self.message
+        )
+    }
+}
+
+/// Validates each config file, printing every problem found. Returns whether
+/// all files are free of errors; warnings alone don't fail validation.
+pub fn run(files: &[String]) -> bool {
+    let mut valid = true;
+    for file in files {
+        valid &= validate_file(file);
+    }
+    valid
+}
+
+fn validate_file(file: &str) -> bool {
+    let content = match std::fs::read_to_string(file) {
+        Ok(content) => content,
+        Err(e) => {
+            println!("{}: error: {}", file, e);
+            return false;
+        }
+    };
+
+    let lines = LineIndex::parse(&content);
+    let config = match Config::parse(&content) {
+        Ok(config) => config,
+        Err(LoadError::Yaml(e)) => {
+            match e.location() {
+                Some(location) => println!("{}:{}: error: {}", file, location.line(), e),
+                None => println!("{}: error: {}", file, e),
+            }
+            return false;
+        }
+        Err(LoadError::Problems(problems)) => {
+            for problem in &problems {
+                println!("{}", problem.display(file, &lines));
+            }
+            println!("❌ {}: {} error(s)", file, problems.len());
+            return false;
+        }
+    };
+
+    let problems = check(&config);
+    for problem in &problems {
+        println!("{}", problem.display(file, &lines));
+    }
+
+    let errors = problems
+        .iter()
+        .filter(|p| p.severity == Severity::Error)
+        .count();
+    let warnings = problems.len() - errors;
+    if errors > 0 {
+        println!("❌ {}: {} error(s), {} warning(s)", file, errors, warnings);
+    } else if warnings > 0 {
+        println!("⚠️  {}: valid with {} warning(s)", file, warnings);
+    } else {
+        println!(
+            "✅ {}: valid ({} targets, {} alerts)",
+            file,
+            config.targets.len(),
+            config.alerts.len()
+        );
+    }
+    errors == 0
+}
+
+/// Runs every semantic check on `config`, collecting all problems rather than
+/// stopping at the first.
+pub fn check(config: &Config) -> Vec<Problem> {
+    let mut problems = Vec::new();
+    check_targets(config, &mut problems);
+    check_groups(config, &mut problems);
+    check_settings(config, &mut problems);
+    check_alerts(config, &mut problems);
+    problems
+}
+
+fn check_targets(config: &Config, problems: &mut Vec<Problem>) {
+    if config.targets.is_empty() {
+        problems.push(Problem::warning("targets", "no targets defined"));
+    }
+
+    let mut names: HashMap<&str, usize> = HashMap::new();
+    for (i, target) in config.targets.iter().enumerate() {
+        let path = format!("targets[{}]", i);
+
+        if target.name.trim().is_empty() {
+            problems.push(Problem::error(
+                format!("{}.name", path),
+                "name must not be empty",
+            ));
+        } else if let Some(first) = names.get(target.name.as_str()) {
+            problems.push(Problem::error(
+                format!("{}.name", path),
+                format!(
+                    "duplicate target name '{}' (first defined at targets[{}])",
+                    target.name, first
+                ),
+            ));
+        } else {
+            names.insert(&target.name, i);
+        }
+
+        if let Err(e) = check_http_url(&target.url) {
+            problems.push(Problem::error(format!("{}.url", path), e));
+        }
+
+        check_seconds(
+            &format!("{}.interval_seconds", path),
+            target.interval_seconds,
+            problems,
+        );
+        check_seconds(
+            &format!("{}.timeout_seconds", path),
+            target.timeout_seconds,
+            problems,
+        );
+        if target.interval_seconds > 0.0 && target.timeout_seconds > target.interval_seconds {
+            problems.push(Problem::warning(
+                format!("{}.timeout_seconds", path),
+                format!(
+                    "timeout ({}s) is longer than the interval ({}s)",
+                    target.timeout_seconds, target.interval_seconds
+                ),
+            ));
+        }
+
+        for code in &target.expected_status {
+            if !(100..=599).contains(code) {
+                problems.push(Problem::error(
+                    format!("{}.expected_status", path),
+                    format!("{} is not a valid HTTP status code", code),
+                ));
+            }
+        }
+
+        for (j, assertion) in target.assertions.iter().enumerate() {
+            if let Err(e) = crate::assertions::validate(assertion) {
+                problems.push(Problem::error(format!("{}.assertions[{}]", path, j), e));
+            }
+        }
+
+        let bodies = [
+            target.body.is_some(),
+            target.body_file.is_some(),
+            target.json.is_some(),
+        ];
+        if bodies.iter().filter(|set| **set).count() > 1 {
+            problems.push(Problem::error(
+                path.clone(),
+                "only one of body, body_file and json may be set",
+            ));
+        }
+
+        for name in target.alerts.iter().flatten() {
+            if !config.alerts.iter().any(|alert| alert.name == *name) {
+                problems.push(Problem::error(
+                    format!("{}.alerts", path),
+                    format!("unknown alert '{}'", name),
+                ));
+            }
+        }
+
+        if let Some(overrides) = &target.health {
+            let thresholds = config.settings.health.with_overrides(overrides);
+            check_thresholds(&format!("{}.health", path), &thresholds, problems);
+        }
+    }
+}
+
+fn check_groups(config: &Config, problems: &mut Vec<Problem>) {
+    for name in config.groups.keys() {
+        if !config
+            .targets
+            .iter()
+            .any(|t| t.group.as_ref() == Some(name))
+        {
+            problems.push(Problem::warning(
+                format!("groups.{}", name),
+                "group is not used by any target",
+            ));
+        }
+    }
+}
+
+fn check_settings(config: &Config, problems: &mut Vec<Problem>) {
+    let settings = &config.settings;
+    check_seconds(
+        "settings.default_interval",
+        settings.default_interval,
+        problems,
+    );
+    check_seconds(
+        "settings.default_timeout",
+        settings.default_timeout,
+        problems,
+    );
+    check_thresholds("settings.health", &settings.health, problems);
+
+    let listeners = [
+        ("metrics_listen", &settings.metrics_listen),
+        ("status_listen", &settings.status_listen),
+    ];
+    for (setting, addr) in listeners {
+        if let Some(addr) = addr {
+            if addr.parse::<SocketAddr>().is_err() {
+                problems.push(Problem::error(
+                    format!("settings.{}", setting),
+                    format!(
+                        "'{}' is not a valid listen address (expected e.g. 0.0.0.0:9115)",
+                        addr
+                    ),
+                ));
+            }
+        }
+    }
+    if settings.status_listen.is_some() && settings.status_listen == settings.metrics_listen {
+        problems.push(Problem::error(
+            "settings.status_listen",
+            "must not be the same address as metrics_listen",
+        ));
+    }
+
+    if settings.log_rotation.max_size_mb == Some(0) {
+        problems.push(Problem::error(
+            "settings.log_rotation.max_size_mb",
+            "must be greater than 0",
+        ));
+    }
+    if settings.log_rotation.max_age_hours == Some(0) {
+        problems.push(Problem::error(
+            "settings.log_rotation.max_age_hours",
+            "must be greater than 0",
+        ));
+    }
+}
+
+fn check_alerts(config: &Config, problems: &mut Vec<Problem>) {
+    let mut names: HashMap<&str, usize> = HashMap::new();
+    for (i, alert) in config.alerts.iter().enumerate() {
+        let path = format!("alerts[{}]", i);
+
+        // Incidents are tracked by alert name, so duplicates would share state
+        if let Some(first) = names.get(alert.name.as_str()) {
+            problems.push(Problem::error(
+                format!("{}.name", path),
+                format!(
+                    "duplicate alert name '{}' (first defined at alerts[{}])",
+                    alert.name, first
+                ),
+            ));
+        } else {
+            names.insert(&alert.name, i);
+        }
+
+        if let Err(e) = check_http_url(&alert.webhook_url) {
+            problems.push(Problem::error(format!("{}.webhook_url", path), e));
+        }
+
+        if alert.trigger_on.is_empty() {
+            problems.push(Problem::warning(
+                format!("{}.trigger_on", path),
+                "alert has no triggers and will never fire",
+            ));
+        }
+    }
+}
+
+fn check_http_url(value: &str) -> Result<(), String> {
+    let url = url::Url::parse(value).map_err(|e| format!("invalid URL '{}': {}", value, e))?;
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err(format!(
+            "unsupported URL scheme '{}' (expected http or https)",
+            url.scheme()
+        ));
+    }
+    if url.host_str().is_none_or(str::is_empty) {
+        return Err(format!("URL '{}' has no host", value));
+    }
+    Ok(())
+}
+
+fn check_seconds(path: &str, value: f64, problems: &mut Vec<Problem>) {
+    if !value.is_finite() || value <= 0.0 {
+        problems.push(Problem::error(
+            path,
+            format!("must be greater than 0 (got {})", value),
+        ));
+    }
+}
+
+fn check_thresholds(path: &str, thresholds: &HealthThresholds, problems: &mut Vec<Problem>) {
+    if thresholds.degraded_uptime > thresholds.healthy_uptime {
+        problems.push(Problem::error(
+            format!("{}.degraded_uptime", path),
+            format!(
+                "degraded_uptime ({}) is above healthy_uptime ({})",
+                thresholds.degraded_uptime, thresholds.healthy_uptime
+            ),
+        ));
+    }
+    if thresholds.uptime_weight < 0.0 || thresholds.response_time_weight < 0.0 {
+        problems.push(Problem::error(path, "weights must not be negative"));
+    }
+}
+
+/// Maps document paths like `targets[1].url` to the 1-based line they start on.
+#[derive(Debug, Default)]
+pub struct LineIndex {
+    lines: HashMap<String, usize>,
+}
+
+impl LineIndex {
+    /// Indexes `source`. Syntax errors just leave the rest of the document
+    /// unindexed; they are reported by the deserializer instead.
+    pub fn parse(source: &str) -> Self {
+        let mut builder = LineIndexBuilder::default();
+        let _ = Parser::new_from_str(source).load(&mut builder, false);
+        Self {
+            lines: builder.lines,
+        }
+    }
+
+    /// Line of `path`, or of its nearest ancestor present in the document.
+    pub fn line_for(&self, path: &str) -> Option<usize> {
+        let mut path = path;
+        loop {
+            if let Some(line) = self.lines.get(path) {
+                return Some(*line);
+            }
+            let parent = path.rfind(['.', '[']).map(|i| &path[..i])?;
+            path = parent;
+        }
+    }
+}
+
+enum Frame {
+    /// `key` holds the pending key once it has been read and until its value starts
+    Mapping {
+        path: String,
+        key: Option<String>,
+    },
+    Sequence {
+        path: String,
+        index: usize,
+    },
+}
+
+#[derive(Default)]
+struct LineIndexBuilder {
+    stack: Vec<Frame>,
+    lines: HashMap<String, usize>,
+}
+
+impl LineIndexBuilder {
+    /// Path of the node that is starting, or `None` if it is a mapping key.
+    fn next_path(&mut self) -> Option<String> {
+        match self.stack.last_mut() {
+            None => Some(String::new()),
+            Some(Frame::Mapping { path, key }) => key.take().map(|key| join(path, &key)),
+            Some(Frame::Sequence { path, index }) => {
+                let item = format!("{}[{}]", path, index);
+                *index += 1;
+                Some(item)
+            }
+        }
+    }
+
+    fn record(&mut self, path: &str, mark: Marker) {
+        self.lines.entry(path.to_string()).or_insert(mark.line());
+    }
+}
+
+impl MarkedEventReceiver for LineIndexBuilder {
+    fn on_event(&mut self, event: Event, mark: Marker) {
+        match event {
+            Event::Scalar(value, ..) => match self.next_path() {
+                Some(path) => self.record(&path, mark),
+                None => {
+                    if let Some(Frame::Mapping { path, key }) = self.stack.last_mut() {
+                        let key_path = join(path, &value);
+                        *key = Some(value);
+                        self.record(&key_path, mark);
+                    }
+                }
+            },
+            Event::MappingStart(..) => {
+                let path = self.next_path().unwrap_or_default();
+                self.record(&path, mark);
+                self.stack.push(Frame::Mapping { path, key: None });
+            }
+            Event::SequenceStart(..) => {
+                let path = self.next_path().unwrap_or_default();
+                self.record(&path, mark);
+                self.stack.push(Frame::Sequence { path, index: 0 });
+            }
+            Event::MappingEnd | Event::SequenceEnd => {
+                self.stack.pop();
+            }
+            Event::Alias(_) => {
+                self.next_path();
+            }
+            _ => {}
+        }
+    }
+}
+
+fn join(parent: &str, key: &str) -> String {
+    if parent.is_empty() {
+        key.to_string()
+    } else {
+        format!("{}.{}", parent, key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DOC: &str = "\
+targets:
+  - name: api
+    url: http://example.com
+    assertions:
+      - !contains ok
+      - !regex \"x+\"
+  - name: web
+    headers: {Accept: text/html, X-Id: \"1\"}
+settings:
+  default_timeout: 5
+";
+
+    #[test]
+    fn indexes_keys_and_sequence_items() {
+        let lines = LineIndex::parse(DOC);
+        assert_eq!(lines.line_for("targets"), Some(1));
+        assert_eq!(lines.line_for("targets[0]"), Some(2));
+        assert_eq!(lines.line_for("targets[0].url"), Some(3));
+        assert_eq!(lines.line_for("targets[0].assertions[1]"), Some(6));
+        assert_eq!(lines.line_for("targets[1].name"), Some(7));
+        assert_eq!(lines.line_for("targets[1].headers.X-Id"), Some(8));
+        assert_eq!(lines.line_for("settings.default_timeout"), Some(10));
+    }
+
+    #[test]
+    fn falls_back_to_the_nearest_ancestor() {
+        let lines = LineIndex::parse(DOC);
+        // Fields left to their defaults aren't in the document
+        assert_eq!(lines.line_for("targets[1].timeout_seconds"), Some(7));
+        assert_eq!(lines.line_for("settings.health.degraded_uptime"), Some(9));
+        assert_eq!(lines.line_for("targets[0].assertions[1].extra"), Some(6));
+    }
+
+    #[test]
+    fn unknown_paths_have_no_line() {
+        let lines = LineIndex::parse(DOC);
+        assert_eq!(lines.line_for("alerts"), None);
+        assert_eq!(lines.line_for("alerts[0].name"), None);
+    }
+
+    #[test]
+    fn keeps_what_was_indexed_before_a_syntax_error() {
+        let lines = LineIndex::parse("settings:\n  default_timeout: 5\ntargets: [\n");
+        assert_eq!(lines.line_for("settings.default_timeout"), Some(2));
+    }
+}